@@ -1,13 +1,21 @@
 use std::{
     env::{args, Args},
-    net::{Ipv4Addr, SocketAddrV4, UdpSocket},
+    net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, UdpSocket},
 };
 
 use anyhow::Context;
 use dns_starter_rust::message::{
-    HeaderError, Message, OperationCode, ResourceClass, ResourceData, ResourceRecord,
+    CharacterString, Edns, Encode, EncodeError, Label, Message, MessageRef, OperationCode,
+    QuestionType, ResourceClass, ResourceData, ResourceRecord, ResponseCode,
 };
 
+/// The largest UDP payload the server is able to receive, which is advertised over EDNS(0).
+const MAX_UDP_PAYLOAD_SIZE: u16 = 4096;
+
+/// The addresses of every host the server answers for.
+const IPV4_ADDRESS: Ipv4Addr = Ipv4Addr::new(8, 8, 8, 8);
+const IPV6_ADDRESS: Ipv6Addr = Ipv6Addr::new(0x2001, 0x4860, 0x4860, 0, 0, 0, 0, 0x8888);
+
 fn read_resolver(mut args: Args) -> Option<SocketAddrV4> {
     args.next().and_then(|flag| {
         args.next().and_then(|address| {
@@ -22,7 +30,8 @@ fn read_resolver(mut args: Args) -> Option<SocketAddrV4> {
 
 fn main() -> anyhow::Result<()> {
     let udp_socket = UdpSocket::bind("127.0.0.1:2053").expect("Failed to bind to address");
-    let mut buf = [0; 512];
+    let mut buf = [0; MAX_UDP_PAYLOAD_SIZE as usize];
+    let mut response_buf = [0; MAX_UDP_PAYLOAD_SIZE as usize];
 
     let mut args = args();
     args.next();
@@ -35,18 +44,31 @@ fn main() -> anyhow::Result<()> {
             Ok((size, source)) => {
                 println!("Received {} bytes from {}", size, source);
                 let message_buf = &buf[..size];
-                eprintln!("\nPacket: {:?}\n", message_buf);
 
-                let mut message = match resolver {
+                // a malformed query is dropped rather than shutting the server down
+                let reply = match resolver {
                     Some(address) => forward_message(&address, &udp_socket, message_buf),
                     None => quick_reply(message_buf),
-                }?;
+                };
+                let mut message = match reply {
+                    Ok(message) => message,
+                    Err(e) => {
+                        eprintln!("Error handling query from {}: {:#}", source, e);
+                        continue;
+                    }
+                };
 
                 message.respond();
-                let response: Vec<u8> = message.into();
+                let length = match encode_response(message, &mut response_buf) {
+                    Ok(length) => length,
+                    Err(e) => {
+                        eprintln!("Error encoding response to {}: {}", source, e);
+                        continue;
+                    }
+                };
 
                 udp_socket
-                    .send_to(&response, source)
+                    .send_to(&response_buf[..length], source)
                     .expect("Failed to send response");
             }
             Err(e) => {
@@ -59,12 +81,44 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Encodes the response into `buf` within the payload size advertised by the requestor, dropping
+/// the RRsets that don't fit and setting the truncation flag, and returns its length.
+fn encode_response(mut message: Message, buf: &mut [u8]) -> Result<usize, EncodeError> {
+    let limit = buf.len().min(message.max_payload_size() as usize);
+    // The DO bit of the query is copied into the response, see <https://datatracker.ietf.org/doc/html/rfc3225#section-3>
+    if let Some(edns) = &mut message.edns {
+        *edns = Edns {
+            extended_rcode: edns.extended_rcode,
+            dnssec_ok: edns.dnssec_ok,
+            ..Edns::new(MAX_UDP_PAYLOAD_SIZE)
+        };
+    }
+
+    message.truncate_to(limit)?;
+    message.encode(&mut &mut buf[..])
+}
+
+/// The host of a `_Service._Proto.Name` owner name, i.e. `Name`.
+fn service_target(name: &Label) -> Label {
+    let labels = name
+        .0
+        .iter()
+        .skip_while(|label| matches!(label, CharacterString::String(s) if s.starts_with(b"_")));
+    Label(labels.cloned().collect())
+}
+
 fn quick_reply(buf: &[u8]) -> anyhow::Result<Message> {
     let mut message: Message = buf.try_into().context("decoding query message")?;
 
     match message.header.operation_code {
-        OperationCode::StandardQuery => message.header.response = Ok(()),
-        _ => message.header.response = Err(HeaderError::NotImplemented),
+        OperationCode::StandardQuery => message.header.response = ResponseCode::NoError,
+        _ => message.header.response = ResponseCode::NotImplemented,
+    }
+
+    // Only version 0 of EDNS is supported, see <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3>
+    if message.edns.as_ref().is_some_and(|edns| edns.version > 0) {
+        message.set_response_code(ResponseCode::BadVersion);
+        return Ok(message);
     }
 
     let answers = message
@@ -74,7 +128,16 @@ fn quick_reply(buf: &[u8]) -> anyhow::Result<Message> {
             name: q.name.clone(),
             class: ResourceClass::IN,
             time_to_live: 60,
-            data: ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+            data: match q.typ {
+                QuestionType::AAAA => ResourceData::Ipv6Address(IPV6_ADDRESS),
+                QuestionType::SRV => ResourceData::Service {
+                    priority: 10,
+                    weight: 5,
+                    port: 80,
+                    target: service_target(&q.name),
+                },
+                _ => ResourceData::Address(IPV4_ADDRESS),
+            },
         })
         .collect::<Vec<_>>();
 
@@ -92,36 +155,71 @@ fn forward_message(
 ) -> anyhow::Result<Message> {
     let mut message: Message = buf.try_into().context("decoding query message")?;
 
-    let header = {
-        let mut header = message.header.clone();
-        header.question_count = 1;
-        header
-    };
+    let header = message.header.clone();
 
-    let mut inner_buf = [0; 512];
+    let mut inner_buf = [0; MAX_UDP_PAYLOAD_SIZE as usize];
 
     let questions = message.questions.clone();
     for question in questions.into_iter() {
-        let question_message = Message {
+        let mut question_message = Message {
             header: header.clone(),
             questions: vec![question.clone()],
             answers: vec![],
             authorities: vec![],
             additionals: vec![],
+            edns: None,
         };
+        question_message.set_edns(Some(Edns::new(MAX_UDP_PAYLOAD_SIZE)));
 
-        socket.send_to(&Vec::from(question_message), address)?;
+        let length = question_message.encode(&mut &mut inner_buf[..])?;
+        socket.send_to(&inner_buf[..length], address)?;
         let (size, _) = socket.recv_from(&mut inner_buf)?;
-        let mut reply = Message::try_from(&inner_buf[..size])?;
-        if reply.header.answer_count > 0 {
-            message.answer(reply.answers.pop().unwrap());
+        // only the answers of the reply are needed, so the rest of it is never decoded
+        let reply = MessageRef::try_from(&inner_buf[..size])?;
+        for answer in reply.answers() {
+            message.answer(answer?.try_into()?);
         }
     }
 
     match message.header.operation_code {
-        OperationCode::StandardQuery => message.header.response = Ok(()),
-        _ => message.header.response = Err(HeaderError::NotImplemented),
+        OperationCode::StandardQuery => message.header.response = ResponseCode::NoError,
+        _ => message.header.response = ResponseCode::NotImplemented,
     }
 
     Ok(message)
 }
+
+#[cfg(test)]
+mod responding {
+    use super::*;
+    use dns_starter_rust::message::{EdnsOption, QuestionClass};
+
+    #[test]
+    fn copy_dnssec_ok_bit() {
+        for dnssec_ok in [false, true] {
+            let mut message = Message::new(1234);
+            message
+                .ask("codecrafters.io", QuestionType::A, QuestionClass::IN)
+                .unwrap();
+            message.set_edns(Some(Edns {
+                dnssec_ok,
+                options: vec![EdnsOption {
+                    code: 10,
+                    data: vec![0xde, 0xad, 0xbe, 0xef],
+                }],
+                ..Edns::new(1232)
+            }));
+
+            let mut buf = [0; MAX_UDP_PAYLOAD_SIZE as usize];
+            let length = encode_response(message, &mut buf).unwrap();
+            let response = Message::try_from(&buf[..length]).unwrap();
+            assert_eq!(
+                response.edns,
+                Some(Edns {
+                    dnssec_ok,
+                    ..Edns::new(MAX_UDP_PAYLOAD_SIZE)
+                })
+            );
+        }
+    }
+}
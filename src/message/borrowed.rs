@@ -0,0 +1,641 @@
+//! A [`MessageRef`] reads a message in place: its sections are only checked to be well delimited
+//! when it's created, while domain names and resource data are decoded on demand. It may be
+//! converted into an owned [`Message`] when the whole message is needed.
+use super::{
+    resource::take, skip_label, CharacterString, EdnsError, Header, Label, LabelError, Message,
+    MessageParseError, Question, QuestionClass, QuestionParseError, QuestionType, ResourceClass,
+    ResourceData, ResourceDataError, ResourceRecord, ResourceRecordError, ResourceType,
+    MAX_NAME_LENGTH,
+};
+
+/// A borrowed view of the wire format of a [`Message`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageRef<'a> {
+    buf: &'a [u8],
+    header: Header,
+
+    /// The offsets of the question, answer, authority and additional sections, and of the end of
+    /// the message.
+    sections: [usize; 5],
+}
+
+impl<'a> MessageRef<'a> {
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// The whole message, as it was received
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    pub fn questions(&self) -> Questions<'a> {
+        Questions {
+            message: self.buf,
+            offset: self.sections[0],
+            remaining: self.header.question_count,
+        }
+    }
+
+    pub fn answers(&self) -> Records<'a> {
+        self.records(1, self.header.answer_count)
+    }
+
+    pub fn authorities(&self) -> Records<'a> {
+        self.records(2, self.header.authority_count)
+    }
+
+    /// The records of the additional section, including the OPT pseudo record if any
+    pub fn additionals(&self) -> Records<'a> {
+        self.records(3, self.header.addtional_count)
+    }
+
+    fn records(&self, section: usize, count: u16) -> Records<'a> {
+        Records {
+            message: self.buf,
+            offset: self.sections[section],
+            remaining: count,
+        }
+    }
+}
+
+/// The length of the question at the start of `value`.
+fn skip_question(value: &[u8]) -> Result<usize, QuestionParseError> {
+    let offset = skip_label(value)?;
+    match value.len() - offset {
+        0 | 1 => Err(QuestionParseError::MissingTypeAndClass),
+        2 | 3 => Err(QuestionParseError::MissingClass),
+        _ => Ok(offset + 4),
+    }
+}
+
+/// The length of the resource record at the start of `value`.
+fn skip_record(value: &[u8]) -> Result<usize, ResourceRecordError> {
+    let offset = skip_label(value)? + 10;
+    let Some(header) = value.get(..offset) else {
+        return Err(ResourceRecordError::Truncated);
+    };
+
+    let length = u16::from_be_bytes([header[offset - 2], header[offset - 1]]);
+    match value.len() - offset {
+        remaining if remaining < length as usize => {
+            Err(ResourceRecordError::DataOverrun { length, remaining })
+        }
+        _ => Ok(offset + length as usize),
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for MessageRef<'a> {
+    type Error = MessageParseError;
+
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        if value.len() < 12 {
+            return Err(MessageParseError::ShortBuffer);
+        }
+        let header: Header = value[..12].try_into()?;
+
+        let mut offset = 12;
+        let mut sections = [offset; 5];
+
+        for _ in 0..header.question_count {
+            offset += skip_question(&value[offset..])?;
+        }
+        sections[1] = offset;
+
+        let counts = [
+            header.answer_count,
+            header.authority_count,
+            header.addtional_count,
+        ];
+        for (section, count) in counts.into_iter().enumerate() {
+            for _ in 0..count {
+                offset += skip_record(&value[offset..])?;
+            }
+            sections[section + 2] = offset;
+        }
+
+        // the counts of the header must account for the whole message
+        if offset < value.len() {
+            return Err(MessageParseError::TrailingData(value.len() - offset));
+        }
+
+        Ok(Self {
+            buf: value,
+            header,
+            sections,
+        })
+    }
+}
+
+/// A domain name inside of a message, whose compression pointers are followed on demand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameRef<'a> {
+    message: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> NameRef<'a> {
+    /// The name starting at `offset` inside of `message`, which may be out of bounds: reading its
+    /// labels then fails with [`LabelError::IncompleteBuffer`].
+    pub fn new(message: &'a [u8], offset: usize) -> Self {
+        Self { message, offset }
+    }
+
+    /// The labels of the name, from the leftmost one to the one preceding the root
+    pub fn labels(&self) -> NameLabels<'a> {
+        NameLabels {
+            message: self.message,
+            offset: Some(self.offset),
+            start: self.offset,
+            end: self.offset,
+            length: 1,
+        }
+    }
+
+    /// Copies the labels of the name into a [`Label`] without compression pointers
+    pub fn to_label(&self) -> Result<Label, LabelError> {
+        self.labels()
+            .map(|label| label.map(|label| CharacterString::String(label.to_vec())))
+            .collect::<Result<_, _>>()
+            .map(Label)
+    }
+}
+
+/// An iterator over the labels of a [`NameRef`].
+///
+/// Every pointer must refer to an offset before the labels it follows, so that they can't form a
+/// loop ([RFC1035 4.1.4]), and the name must be at most [`MAX_NAME_LENGTH`] octets.
+///
+/// [RFC1035 4.1.4]: <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4>
+#[derive(Debug, Clone)]
+pub struct NameLabels<'a> {
+    message: &'a [u8],
+    offset: Option<usize>,
+    /// The offset of the labels being read, i.e. the start of the name or the last pointer target
+    start: usize,
+    /// The end of the furthest octet read so far
+    end: usize,
+    /// The length of the wire format of the labels read so far, including the root
+    length: usize,
+}
+
+impl<'a> NameLabels<'a> {
+    fn next_label(&mut self, offset: usize) -> Result<Option<&'a [u8]>, LabelError> {
+        use LabelError::*;
+        let mut offset = offset;
+
+        loop {
+            // an offset past the end is only possible for a name created out of bounds
+            match self.message.get(offset..).unwrap_or_default() {
+                [] => return Err(IncompleteBuffer),
+                [0, ..] => return Ok(None),
+                [octet, rest @ ..] if octet & 0b1100_0000 == 0b1100_0000 => {
+                    let [low, ..] = rest else {
+                        return Err(IncompleteBuffer);
+                    };
+                    let pointer = u16::from_be_bytes([octet & 0b0011_1111, *low]);
+
+                    self.end = self.end.max(offset + 2);
+                    offset = match pointer as usize {
+                        target if target >= self.message.len() => {
+                            return Err(InvalidPointer(pointer))
+                        }
+                        target if target < self.start => target,
+                        target if target < self.end => return Err(PointerLoop(pointer)),
+                        _ => return Err(ForwardPointer(pointer)),
+                    };
+                    self.start = offset;
+                }
+                [octet, ..] if octet & 0b1100_0000 != 0 => return Err(ReservedLabelType(*octet)),
+                [count, rest @ ..] => {
+                    let label = rest
+                        .get(..*count as usize)
+                        .ok_or(FalseEncodedLength(*count))?;
+
+                    self.length += label.len() + 1;
+                    if self.length > MAX_NAME_LENGTH {
+                        return Err(NameTooLong(self.length));
+                    }
+
+                    self.offset = Some(offset + 1 + label.len());
+                    self.end = self.end.max(offset + 1 + label.len());
+                    return Ok(Some(label));
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for NameLabels<'a> {
+    type Item = Result<&'a [u8], LabelError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset.take()?;
+        self.next_label(offset).transpose()
+    }
+}
+
+/// A question read in place, see [`Question`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuestionRef<'a> {
+    pub name: NameRef<'a>,
+    pub typ: QuestionType,
+    pub class: QuestionClass,
+}
+
+impl TryFrom<QuestionRef<'_>> for Question {
+    type Error = QuestionParseError;
+
+    fn try_from(value: QuestionRef<'_>) -> Result<Self, Self::Error> {
+        Ok(Question {
+            name: value.name.to_label()?,
+            typ: value.typ,
+            class: value.class,
+        })
+    }
+}
+
+/// An iterator over the questions of a [`MessageRef`], which ends after the first error.
+#[derive(Debug, Clone)]
+pub struct Questions<'a> {
+    message: &'a [u8],
+    offset: usize,
+    remaining: u16,
+}
+
+impl<'a> Questions<'a> {
+    fn next_question(&mut self) -> Result<QuestionRef<'a>, QuestionParseError> {
+        let buf = self.message.get(self.offset..).unwrap_or_default();
+        let length = skip_question(buf)?;
+        let [t0, t1, c0, c1] = buf[length - 4..length] else {
+            unreachable!("a question ends with its type and class");
+        };
+
+        let name = NameRef {
+            message: self.message,
+            offset: self.offset,
+        };
+        self.offset += length;
+
+        Ok(QuestionRef {
+            name,
+            typ: u16::from_be_bytes([t0, t1]).into(),
+            class: u16::from_be_bytes([c0, c1]).into(),
+        })
+    }
+}
+
+impl<'a> Iterator for Questions<'a> {
+    type Item = Result<QuestionRef<'a>, QuestionParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+
+        let question = self.next_question();
+        if question.is_err() {
+            self.remaining = 0;
+        }
+        Some(question)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+impl ExactSizeIterator for Questions<'_> {}
+
+/// A resource record read in place, whose data is only decoded on demand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordRef<'a> {
+    pub name: NameRef<'a>,
+    pub typ: ResourceType,
+    pub class: ResourceClass,
+    pub time_to_live: u32,
+
+    /// The wire format of the data
+    pub data: &'a [u8],
+
+    /// The message up to the end of the data, against which the names of the data are
+    /// decompressed, as they may only point backwards
+    message: &'a [u8],
+}
+
+impl RecordRef<'_> {
+    /// Decodes the data of the record, see [`ResourceData::parse_in_class`], where the
+    /// compressed names are expanded against the message preceding the end of the data.
+    pub fn data(&self) -> Result<ResourceData, ResourceDataError> {
+        let mut data = ResourceData::parse_in_class(self.typ, self.class, self.data)?;
+        data.expand_names(self.message)?;
+        Ok(data)
+    }
+}
+
+impl TryFrom<RecordRef<'_>> for ResourceRecord {
+    type Error = ResourceRecordError;
+
+    fn try_from(value: RecordRef<'_>) -> Result<Self, Self::Error> {
+        let data = value.data()?;
+        Ok(ResourceRecord {
+            name: value.name.to_label()?,
+            class: value.class,
+            time_to_live: value.time_to_live,
+            data,
+        })
+    }
+}
+
+/// An iterator over the records of a section of a [`MessageRef`], which ends after the first
+/// error.
+#[derive(Debug, Clone)]
+pub struct Records<'a> {
+    message: &'a [u8],
+    offset: usize,
+    remaining: u16,
+}
+
+impl<'a> Records<'a> {
+    fn next_record(&mut self) -> Result<RecordRef<'a>, ResourceRecordError> {
+        let buf = self.message.get(self.offset..).unwrap_or_default();
+        let length = skip_record(buf)?;
+        let mut fields = &buf[skip_label(buf)?..length];
+        let [t0, t1, c0, c1, l0, l1, l2, l3, _, _] =
+            take(&mut fields).expect("a record has a type, class, TTL and RDLENGTH");
+
+        let name = NameRef {
+            message: self.message,
+            offset: self.offset,
+        };
+        self.offset += length;
+
+        Ok(RecordRef {
+            name,
+            typ: u16::from_be_bytes([t0, t1]).into(),
+            class: u16::from_be_bytes([c0, c1]).into(),
+            time_to_live: u32::from_be_bytes([l0, l1, l2, l3]),
+            data: fields,
+            message: &self.message[..self.offset],
+        })
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Result<RecordRef<'a>, ResourceRecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+
+        let record = self.next_record();
+        if record.is_err() {
+            self.remaining = 0;
+        }
+        Some(record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+impl ExactSizeIterator for Records<'_> {}
+
+impl TryFrom<MessageRef<'_>> for Message {
+    type Error = MessageParseError;
+
+    fn try_from(value: MessageRef<'_>) -> Result<Self, Self::Error> {
+        let questions = value
+            .questions()
+            .map(|question| question.and_then(Question::try_from))
+            .collect::<Result<_, _>>()?;
+        let answers = value
+            .answers()
+            .map(|answer| answer.and_then(ResourceRecord::try_from))
+            .collect::<Result<_, _>>()?;
+        let authorities = value
+            .authorities()
+            .map(|authority| authority.and_then(ResourceRecord::try_from))
+            .collect::<Result<_, _>>()?;
+
+        let mut additionals = vec![];
+        let mut edns = None;
+        for additional in value.additionals() {
+            let additional = additional.and_then(ResourceRecord::try_from)?;
+
+            if additional.typ() != ResourceType::OPT {
+                additionals.push(additional);
+            } else if edns.is_none() {
+                edns = Some(additional.try_into()?);
+            } else {
+                return Err(EdnsError::Duplicate.into());
+            }
+        }
+
+        Ok(Self {
+            header: value.header,
+            questions,
+            answers,
+            authorities,
+            additionals,
+            edns,
+        })
+    }
+}
+
+#[cfg(test)]
+mod reading {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        let mut message = Message::new(1234);
+        message
+            .ask("codecrafters.io", QuestionType::MX, QuestionClass::IN)
+            .unwrap();
+        message.answer(ResourceRecord {
+            name: Label::parse_str("codecrafters.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::MailExchange {
+                preference: 10,
+                exchange: Label::parse_str("mail.codecrafters.io").unwrap(),
+            },
+        });
+        message.add(ResourceRecord {
+            name: Label::parse_str("mail.codecrafters.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+        });
+        message.try_into().unwrap()
+    }
+
+    #[test]
+    fn iterate_sections_in_place() {
+        let buf = sample();
+        let message = MessageRef::try_from(&buf[..]).unwrap();
+
+        let questions = message.questions().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].typ, QuestionType::MX);
+        assert_eq!(
+            questions[0].name.labels().collect::<Result<Vec<_>, _>>(),
+            Ok(vec![&b"codecrafters"[..], b"io"])
+        );
+
+        assert_eq!(message.answers().len(), 1);
+        assert_eq!(message.authorities().len(), 0);
+
+        // the owner of the additional record is compressed into the data of the answer
+        let additional = message.additionals().next().unwrap().unwrap();
+        assert_eq!(additional.typ, ResourceType::A);
+        assert_eq!(additional.data, [8, 8, 8, 8]);
+        assert_eq!(
+            additional.name.to_label(),
+            Label::parse_str("mail.codecrafters.io")
+        );
+        assert_eq!(
+            additional.data(),
+            Ok(ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)))
+        );
+    }
+
+    #[test]
+    fn convert_into_owned_message() {
+        let buf = sample();
+        let message = Message::try_from(MessageRef::try_from(&buf[..]).unwrap()).unwrap();
+        assert_eq!(message.answers.len(), 1);
+        assert_eq!(message.additionals.len(), 1);
+        assert_eq!(Message::try_from(&buf[..]), Ok(message));
+    }
+
+    #[test]
+    fn names_are_resolved_on_demand() {
+        let mut buf = sample();
+        // the name of the answer points to the question
+        buf[12 + 21 + 1] = 0xff;
+
+        let message = MessageRef::try_from(&buf[..]).unwrap();
+        let answer = message.answers().next().unwrap().unwrap();
+        assert_eq!(answer.time_to_live, 60);
+        assert_eq!(
+            answer.name.to_label(),
+            Err(LabelError::InvalidPointer(0xff))
+        );
+    }
+
+    #[test]
+    fn errors_end_iterators() {
+        let buf = [1, b'a', 0, 0, 1];
+        let mut questions = Questions {
+            message: &buf,
+            offset: 0,
+            remaining: 2,
+        };
+        assert_eq!(
+            questions.next(),
+            Some(Err(QuestionParseError::MissingClass))
+        );
+        assert_eq!(questions.next(), None);
+
+        let mut records = Records {
+            message: &buf,
+            offset: 8,
+            remaining: 1,
+        };
+        assert_eq!(
+            records.next(),
+            Some(Err(ResourceRecordError::Label(
+                LabelError::IncompleteBuffer
+            )))
+        );
+        assert_eq!(records.next(), None);
+
+        assert_eq!(
+            NameRef::new(&buf, 8).to_label(),
+            Err(LabelError::IncompleteBuffer)
+        );
+    }
+
+    #[test]
+    fn pointer_loops_end() {
+        #[rustfmt::skip]
+        let buf = [
+            0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0,
+            1, b'a', 0xc0, 12, 0, 1, 0, 1,
+        ];
+
+        let message = MessageRef::try_from(&buf[..]).unwrap();
+        let question = message.questions().next().unwrap().unwrap();
+        assert_eq!(
+            question.name.labels().last(),
+            Some(Err(LabelError::PointerLoop(12)))
+        );
+
+        // a pointer to itself
+        let buf = [0xc0, 0];
+        assert_eq!(
+            NameRef::new(&buf, 0).to_label(),
+            Err(LabelError::PointerLoop(0))
+        );
+
+        // two names pointing to each other
+        #[rustfmt::skip]
+        let buf = [
+            1, b'a', 0xc0, 4,
+            1, b'b', 0xc0, 0,
+        ];
+        assert_eq!(
+            NameRef::new(&buf, 4).to_label(),
+            Err(LabelError::PointerLoop(4))
+        );
+    }
+
+    #[test]
+    fn pointers_go_backwards() {
+        #[rustfmt::skip]
+        let buf = [
+            1, b'a', 0xc0, 4,
+            1, b'b', 0,
+        ];
+        assert_eq!(
+            NameRef::new(&buf, 0).to_label(),
+            Err(LabelError::ForwardPointer(4))
+        );
+        assert_eq!(
+            NameRef::new(&buf, 4).to_label(),
+            Ok(Label::parse_str("b").unwrap())
+        );
+
+        #[rustfmt::skip]
+        let buf = [
+            1, b'b', 0,
+            1, b'a', 0xc0, 0,
+        ];
+        assert_eq!(
+            NameRef::new(&buf, 3).to_label(),
+            Ok(Label::parse_str("a.b").unwrap())
+        );
+    }
+
+    #[test]
+    fn names_are_limited() {
+        // 4 labels of 63 octets and a last one of 2 octets make 257 octets, with the root
+        let mut buf = vec![];
+        for _ in 0..4 {
+            buf.push(63);
+            buf.extend_from_slice(&[b'a'; 63]);
+        }
+        buf.extend_from_slice(&[2, b'a', b'a', 0]);
+        assert_eq!(
+            NameRef::new(&buf, 0).to_label(),
+            Err(LabelError::NameTooLong(257))
+        );
+        assert_eq!(
+            NameRef::new(&buf, 64)
+                .to_label()
+                .map(|name| name.encoded_len()),
+            Ok(196)
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Extension mechanisms for DNS ([EDNS(0)]) are negotiated through a single pseudo resource
+//! record of type [`OPT`] in the additional section, which doesn't hold any DNS data. Its fields
+//! are repurposed as follows:
+//!
+//! ```txt
+//!     +------------+--------------+------------------------------+
+//!     | Field Name | Field Type   | Description                  |
+//!     +------------+--------------+------------------------------+
+//!     | NAME       | domain name  | MUST be 0 (root domain)      |
+//!     | TYPE       | u_int16_t    | OPT (41)                     |
+//!     | CLASS      | u_int16_t    | requestor's UDP payload size |
+//!     | TTL        | u_int32_t    | extended RCODE and flags     |
+//!     | RDLEN      | u_int16_t    | length of all RDATA          |
+//!     | RDATA      | octet stream | {attribute,value} pairs      |
+//!     +------------+--------------+------------------------------+
+//! ```
+//!
+//! where the TTL is laid out as:
+//!
+//! ```txt
+//!                 +0 (MSB)                            +1 (LSB)
+//!      +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+//!   0: |         EXTENDED-RCODE        |            VERSION            |
+//!      +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+//!   2: | DO|                           Z                               |
+//!      +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+//! ```
+//!
+//! [EDNS(0)]: <https://datatracker.ietf.org/doc/html/rfc6891>
+//! [`OPT`]: super::type_class::ResourceType::OPT
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use super::{
+    Counter, Label, ResourceClass, ResourceData, ResourceDataError, ResourceRecord, ResourceType,
+    Sink,
+};
+
+/// Responses without EDNS(0) are limited to 512 bytes over UDP.
+pub const DEFAULT_UDP_PAYLOAD_SIZE: u16 = 512;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edns {
+    /// The number of octets of the largest UDP payload that can be reassembled and delivered in
+    /// the requestor's network stack.
+    pub udp_payload_size: u16,
+
+    /// The upper 8 bits of the 12-bit response code, the lower 4 bits are in the header.
+    pub extended_rcode: u8,
+
+    /// The version of the implementation, only version 0 is defined.
+    pub version: u8,
+
+    /// DNSSEC OK, the requestor is able to accept DNSSEC security RRs ([RFC3225]).
+    ///
+    /// [RFC3225]: <https://datatracker.ietf.org/doc/html/rfc3225>
+    pub dnssec_ok: bool,
+
+    /// Variable options carried by the record.
+    pub options: Vec<EdnsOption>,
+}
+
+impl Edns {
+    /// Create a new version 0 [`Edns`] advertising `udp_payload_size`
+    pub fn new(udp_payload_size: u16) -> Self {
+        Self {
+            udp_payload_size,
+            extended_rcode: 0,
+            version: 0,
+            dnssec_ok: false,
+            options: vec![],
+        }
+    }
+
+    /// The size of the largest UDP payload that may be sent to the requestor, as values lower than
+    /// 512 are treated as 512.
+    pub fn max_payload_size(&self) -> u16 {
+        self.udp_payload_size.max(DEFAULT_UDP_PAYLOAD_SIZE)
+    }
+
+    /// The TTL of the OPT record, holding the extended RCODE, the version and the flags
+    fn time_to_live(&self) -> u32 {
+        let flags = (self.dnssec_ok as u8) << 7;
+        u32::from_be_bytes([self.extended_rcode, self.version, flags, 0])
+    }
+
+    /// Appends the wire format of the OPT record carrying the pseudo record to `buf`, the same
+    /// as the [`ResourceRecord`] it converts into without cloning its options.
+    pub fn write(&self, buf: &mut impl Sink) -> Result<(), ResourceDataError> {
+        let mut length = Counter::new();
+        EdnsOption::write_all(&self.options, &mut length)?;
+        let length =
+            u16::try_from(length.offset()).map_err(|_| ResourceDataError::FieldTooLong {
+                max: u16::MAX as usize,
+                found: length.offset(),
+            })?;
+
+        Label::default().write(buf, None);
+        buf.put_u16(ResourceType::OPT.into());
+        buf.put_u16(self.udp_payload_size);
+        buf.put_u32(self.time_to_live());
+        buf.put_u16(length);
+        EdnsOption::write_all(&self.options, buf)
+    }
+}
+
+/// A single `{attribute,value}` pair of the [`OPT`] record data.
+///
+/// ```txt
+///                 +0 (MSB)                            +1 (LSB)
+///      +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+///   0: |                          OPTION-CODE                          |
+///      +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+///   2: |                         OPTION-LENGTH                         |
+///      +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+///   4: |                                                               |
+///      /                          OPTION-DATA                          /
+///      /                                                               /
+///      +---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+---+
+/// ```
+///
+/// [`OPT`]: super::type_class::ResourceType::OPT
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdnsOption {
+    pub code: u16,
+    pub data: Vec<u8>,
+}
+
+impl EdnsOption {
+    /// Appends the wire format of `options` to `buf`, which is the data of the OPT record.
+    ///
+    /// Fails on the first option whose data is longer than its OPTION-LENGTH can count.
+    pub fn write_all(options: &[EdnsOption], buf: &mut impl Sink) -> Result<(), ResourceDataError> {
+        for option in options {
+            let length =
+                u16::try_from(option.data.len()).map_err(|_| ResourceDataError::FieldTooLong {
+                    max: u16::MAX as usize,
+                    found: option.data.len(),
+                })?;
+            buf.put_u16(option.code);
+            buf.put_u16(length);
+            buf.put_slice(&option.data);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdnsError {
+    /// The record isn't of type [`OPT`][super::type_class::ResourceType::OPT]
+    NotOpt,
+    /// The owner of the record isn't the root domain
+    NonRootName,
+    /// A message carries more than one OPT record
+    Duplicate,
+}
+
+impl Display for EdnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use EdnsError::*;
+        match self {
+            NotOpt => "EDNS must be carried by an OPT record".fmt(f),
+            NonRootName => "the owner of an OPT record must be the root domain".fmt(f),
+            Duplicate => "a message must not carry more than one OPT record".fmt(f),
+        }
+    }
+}
+
+impl Error for EdnsError {}
+
+impl TryFrom<ResourceRecord> for Edns {
+    type Error = EdnsError;
+
+    fn try_from(value: ResourceRecord) -> Result<Self, Self::Error> {
+        let ResourceData::Opt(options) = value.data else {
+            return Err(EdnsError::NotOpt);
+        };
+
+        if value.name.domain_count() != 0 {
+            return Err(EdnsError::NonRootName);
+        }
+
+        let [extended_rcode, version, flags, _] = value.time_to_live.to_be_bytes();
+
+        Ok(Self {
+            udp_payload_size: value.class.into(),
+            extended_rcode,
+            version,
+            dnssec_ok: flags & 0b1000_0000 != 0,
+            options,
+        })
+    }
+}
+
+impl From<Edns> for ResourceRecord {
+    fn from(value: Edns) -> Self {
+        ResourceRecord {
+            name: Label::default(),
+            class: ResourceClass::from(value.udp_payload_size),
+            time_to_live: value.time_to_live(),
+            data: ResourceData::Opt(value.options),
+        }
+    }
+}
+
+#[cfg(test)]
+mod parsing {
+    use super::*;
+    use crate::message::{Message, ResourceType};
+
+    #[test]
+    fn round_trip_opt_record() {
+        #[rustfmt::skip]
+        let buf = [
+            0,
+            0, 41, 0x10, 0x00, 0, 0, 0x80, 0, 0, 8,
+            0, 10, 0, 4, 0xde, 0xad, 0xbe, 0xef,
+        ];
+
+        let record = ResourceRecord::try_from(&buf[..]).unwrap();
+        let edns = Edns::try_from(record.clone()).unwrap();
+        assert_eq!(
+            edns,
+            Edns {
+                udp_payload_size: 4096,
+                extended_rcode: 0,
+                version: 0,
+                dnssec_ok: true,
+                options: vec![EdnsOption {
+                    code: 10,
+                    data: vec![0xde, 0xad, 0xbe, 0xef]
+                }],
+            }
+        );
+        let mut written = vec![];
+        edns.write(&mut written).unwrap();
+        assert_eq!(written, buf);
+
+        assert_eq!(ResourceRecord::from(edns), record);
+        assert_eq!(Vec::try_from(record).unwrap(), buf);
+    }
+
+    #[test]
+    fn reject_non_root_owner() {
+        let record = ResourceRecord {
+            name: Label::parse_str("example").unwrap(),
+            ..Edns::new(1232).into()
+        };
+        assert_eq!(Edns::try_from(record), Err(EdnsError::NonRootName));
+    }
+
+    #[test]
+    fn truncated_option() {
+        assert!(ResourceData::parse(ResourceType::OPT, &[0, 10, 0, 4, 0xde]).is_err());
+    }
+
+    #[test]
+    fn small_payload_sizes_without_options() {
+        // the classes ANY and NONE, whose empty data is otherwise left unparsed
+        for size in [254, 255] {
+            #[rustfmt::skip]
+            let buf = [
+                0,
+                0, 41, 0, size, 0, 0, 0, 0, 0, 0,
+            ];
+
+            let record = ResourceRecord::try_from(&buf[..]).unwrap();
+            assert_eq!(record.data, ResourceData::Opt(vec![]));
+            assert_eq!(Edns::try_from(record), Ok(Edns::new(size as u16)));
+
+            let mut message = vec![0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+            message.extend_from_slice(&buf);
+            let message = Message::try_from(&message[..]).unwrap();
+            assert_eq!(message.edns, Some(Edns::new(size as u16)));
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Binary-to-text encodings used by the presentation format of resource data.
+use std::fmt::{self, Write};
+
+/// Writes `bytes` as lowercase hexadecimal digits.
+pub fn write_hex(f: &mut impl Write, bytes: &[u8]) -> fmt::Result {
+    bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+}
+
+/// Decodes a sequence of hexadecimal digits, ignoring any whitespace in between.
+pub fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    let digits = value
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| (c as char).to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<Vec<_>>>()?;
+
+    match digits.len() % 2 {
+        0 => Some(
+            digits
+                .chunks(2)
+                .map(|pair| pair[0] << 4 | pair[1])
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Writes `bytes` between double quotes, escaping non-printable characters as `\DDD`.
+pub fn write_quoted(f: &mut impl Write, bytes: &[u8]) -> fmt::Result {
+    f.write_char('"')?;
+    for &c in bytes {
+        match c {
+            b'"' | b'\\' => write!(f, "\\{}", c as char)?,
+            0x20..=0x7e => f.write_char(c as char)?,
+            _ => write!(f, "\\{c:03}")?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Resolves the `\X` and `\DDD` escapes of the presentation format.
+pub fn decode_escaped(value: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut chars = value.bytes();
+
+    while let Some(c) = chars.next() {
+        if c != b'\\' {
+            bytes.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            digit @ b'0'..=b'9' => {
+                let rest = [chars.next(), chars.next()];
+                let [Some(d1 @ b'0'..=b'9'), Some(d2 @ b'0'..=b'9')] = rest else {
+                    return None;
+                };
+                let code = [digit, d1, d2]
+                    .iter()
+                    .fold(0u16, |code, d| code * 10 + (d - b'0') as u16);
+                bytes.push(code.try_into().ok()?);
+            }
+            c => bytes.push(c),
+        }
+    }
+
+    Some(bytes)
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+const BASE32HEX: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+
+/// Writes `bytes` in the padded base64 encoding of [RFC4648 4].
+///
+/// [RFC4648 4]: <https://datatracker.ietf.org/doc/html/rfc4648#section-4>
+pub fn write_base64(f: &mut impl Write, bytes: &[u8]) -> fmt::Result {
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (index, &byte)| {
+                group | (byte as u32) << (16 - 8 * index)
+            });
+
+        for index in 0..4 {
+            match index <= chunk.len() {
+                true => {
+                    f.write_char(BASE64[(group >> (18 - 6 * index)) as usize & 0x3f] as char)?
+                }
+                false => f.write_char('=')?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decodes padded base64, ignoring any whitespace in between.
+pub fn decode_base64(value: &str) -> Option<Vec<u8>> {
+    let digits = value
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+
+    if digits.len() % 4 != 0 {
+        return None;
+    }
+
+    let mut bytes = vec![];
+    for (chunk_index, chunk) in digits.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && chunk_index + 1 != digits.len() / 4) {
+            return None;
+        }
+
+        let mut group = 0u32;
+        for &c in chunk[..4 - padding].iter() {
+            let digit = BASE64.iter().position(|&d| d == c)?;
+            group = group << 6 | digit as u32;
+        }
+        group <<= 6 * padding;
+
+        bytes.extend_from_slice(&group.to_be_bytes()[1..4 - padding]);
+    }
+    Some(bytes)
+}
+
+/// Writes `bytes` in the unpadded base32 encoding with the (lowercase) extended hex alphabet of
+/// [RFC4648 7], as used by NSEC3 records.
+///
+/// [RFC4648 7]: <https://datatracker.ietf.org/doc/html/rfc4648#section-7>
+pub fn write_base32hex(f: &mut impl Write, bytes: &[u8]) -> fmt::Result {
+    for chunk in bytes.chunks(5) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u64, |group, (index, &byte)| {
+                group | (byte as u64) << (32 - 8 * index)
+            });
+
+        for index in 0..(chunk.len() * 8).div_ceil(5) {
+            f.write_char(BASE32HEX[(group >> (35 - 5 * index)) as usize & 0x1f] as char)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decodes unpadded (case insensitive) base32hex, ignoring any whitespace in between.
+pub fn decode_base32hex(value: &str) -> Option<Vec<u8>> {
+    let digits = value
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| {
+            BASE32HEX
+                .iter()
+                .position(|&d| d == c.to_ascii_lowercase())
+                .map(|digit| digit as u64)
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut bytes = vec![];
+    for chunk in digits.chunks(8) {
+        let length = chunk.len() * 5 / 8;
+        if !matches!(chunk.len(), 2 | 4 | 5 | 7 | 8) {
+            return None;
+        }
+
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u64, |group, (index, digit)| {
+                group | digit << (35 - 5 * index)
+            });
+
+        bytes.extend_from_slice(&group.to_be_bytes()[3..3 + length]);
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod hex {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut text = String::new();
+        write_hex(&mut text, &[0x0a, 0x00, 0xff, 0x3c]).unwrap();
+        assert_eq!(text, "0a00ff3c");
+        assert_eq!(decode_hex("0A00 ff3c"), Some(vec![0x0a, 0x00, 0xff, 0x3c]));
+        assert_eq!(decode_hex(""), Some(vec![]));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+    }
+}
+
+#[cfg(test)]
+mod base64 {
+    use super::*;
+
+    #[test]
+    fn rfc4648_vectors() {
+        for (bytes, text) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            let mut encoded = String::new();
+            write_base64(&mut encoded, bytes.as_bytes()).unwrap();
+            assert_eq!(encoded, text);
+            assert_eq!(decode_base64(text).as_deref(), Some(bytes.as_bytes()));
+        }
+
+        assert_eq!(decode_base64("Zm9v YmFy"), Some(b"foobar".to_vec()));
+        assert_eq!(decode_base64("Zm9"), None);
+        assert_eq!(decode_base64("Zg==Zg=="), None);
+        assert_eq!(decode_base64("Z==="), None);
+        assert_eq!(decode_base64("Zm9*"), None);
+    }
+}
+
+#[cfg(test)]
+mod base32hex {
+    use super::*;
+
+    #[test]
+    fn rfc4648_vectors() {
+        for (bytes, text) in [
+            ("", ""),
+            ("f", "co"),
+            ("fo", "cpng"),
+            ("foo", "cpnmu"),
+            ("foob", "cpnmuog"),
+            ("fooba", "cpnmuoj1"),
+            ("foobar", "cpnmuoj1e8"),
+        ] {
+            let mut encoded = String::new();
+            write_base32hex(&mut encoded, bytes.as_bytes()).unwrap();
+            assert_eq!(encoded, text);
+            assert_eq!(decode_base32hex(text).as_deref(), Some(bytes.as_bytes()));
+        }
+
+        assert_eq!(decode_base32hex("CPNMUOJ1E8"), Some(b"foobar".to_vec()));
+        assert_eq!(decode_base32hex("CPN"), None);
+        assert_eq!(decode_base32hex("CPNW"), None);
+    }
+}
@@ -6,7 +6,7 @@
 //!     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
 //!     |                      ID                       |
 //!     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
-//!     |QR|   Opcode  |AA|TC|RD|RA|   Z    |   RCODE   |
+//!     |QR|   Opcode  |AA|TC|RD|RA| Z|AD|CD|   RCODE   |
 //!     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
 //!     |                    QDCOUNT                    |
 //!     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
@@ -24,6 +24,8 @@ use std::{
 
 use bytes::{Buf, BufMut};
 
+use super::Sink;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Header {
     /// A random identifier is assigned to query packets. Response packets must reply with the same
@@ -54,8 +56,32 @@ pub struct Header {
     /// Denotes whether recursive query support is available in the name server.
     pub recursion_available: bool,
 
+    /// Reserved for future use, must be zero in all queries and responses but is kept as
+    /// received.
+    pub reserved: bool,
+
+    /// Set in a response when all of the data in the answer and authority sections has been
+    /// authenticated by the server according to its policies ([RFC4035 3.2.3]).
+    ///
+    /// [RFC4035 3.2.3]: <https://datatracker.ietf.org/doc/html/rfc4035#section-3.2.3>
+    pub authentic_data: bool,
+
+    /// Set in a query to indicate that pending (non-authenticated) data is acceptable to the
+    /// resolver sending the query ([RFC4035 3.2.2]).
+    ///
+    /// [RFC4035 3.2.2]: <https://datatracker.ietf.org/doc/html/rfc4035#section-3.2.2>
+    pub checking_disabled: bool,
+
     /// Response status code.
-    pub response: Result<(), HeaderError>,
+    ///
+    /// Only the lower 4 bits are carried by the header, the upper 8 bits of an extended code are
+    /// in the [`Edns`][super::Edns] of the message, see [`Message::response_code`]. An extended
+    /// code set here is only written whole as part of a message, which moves its upper bits into
+    /// the OPT record like [`Message::set_response_code`] does.
+    ///
+    /// [`Message::response_code`]: super::Message::response_code
+    /// [`Message::set_response_code`]: super::Message::set_response_code
+    pub response: ResponseCode,
 
     /// The number of entries in the question section.
     pub question_count: u16,
@@ -85,7 +111,9 @@ pub struct HeaderBuilder {
     truncated_message: Option<bool>,
     recursion_desired: Option<bool>,
     recursion_available: Option<bool>,
-    response: Option<HeaderError>,
+    authentic_data: Option<bool>,
+    checking_disabled: Option<bool>,
+    response: Option<ResponseCode>,
     question_count: Option<u16>,
     answer_count: Option<u16>,
     authority_count: Option<u16>,
@@ -146,6 +174,20 @@ impl HeaderBuilder {
         }
     }
 
+    pub fn authentic_data(self, authentic_data: bool) -> Self {
+        Self {
+            authentic_data: Some(authentic_data),
+            ..self
+        }
+    }
+
+    pub fn checking_disabled(self, checking_disabled: bool) -> Self {
+        Self {
+            checking_disabled: Some(checking_disabled),
+            ..self
+        }
+    }
+
     pub fn question_count(self, question_count: u16) -> Self {
         Self {
             question_count: Some(question_count),
@@ -153,8 +195,11 @@ impl HeaderBuilder {
         }
     }
 
-    pub fn response(self, response: Option<HeaderError>) -> Self {
-        Self { response, ..self }
+    pub fn response(self, response: ResponseCode) -> Self {
+        Self {
+            response: Some(response),
+            ..self
+        }
     }
 
     pub fn answer_count(self, answer_count: u16) -> Self {
@@ -190,10 +235,10 @@ impl HeaderBuilder {
             truncated_message: self.truncated_message.unwrap_or(false),
             recursion_desired: self.recursion_desired.unwrap_or(false),
             recursion_available: self.recursion_available.unwrap_or(false),
-            response: match self.response {
-                Some(err) => Err(err),
-                None => Ok(()),
-            },
+            reserved: false,
+            authentic_data: self.authentic_data.unwrap_or(false),
+            checking_disabled: self.checking_disabled.unwrap_or(false),
+            response: self.response.unwrap_or(ResponseCode::NoError),
             question_count: self.question_count.unwrap_or(0),
             answer_count: self.answer_count.unwrap_or(0),
             authority_count: self.authority_count.unwrap_or(0),
@@ -215,9 +260,34 @@ pub enum OperationCode {
     StandardQuery,
     InverseQuery,
     StatusRequest,
+
+    /// A zone change notification sent by a primary server to its secondaries ([RFC1996]).
+    ///
+    /// [RFC1996]: <https://datatracker.ietf.org/doc/html/rfc1996>
+    Notify,
+
+    /// A dynamic update of the records of a zone ([RFC2136]), see [`Update`][super::Update].
+    ///
+    /// [RFC2136]: <https://datatracker.ietf.org/doc/html/rfc2136>
+    Update,
+
     Reserved(u16),
 }
 
+impl From<u16> for OperationCode {
+    fn from(value: u16) -> Self {
+        use OperationCode::*;
+        match value {
+            0 => StandardQuery,
+            1 => InverseQuery,
+            2 => StatusRequest,
+            4 => Notify,
+            5 => Update,
+            code => Reserved(code),
+        }
+    }
+}
+
 impl From<OperationCode> for u16 {
     fn from(value: OperationCode) -> Self {
         use OperationCode::*;
@@ -225,16 +295,39 @@ impl From<OperationCode> for u16 {
             StandardQuery => 0,
             InverseQuery => 1,
             StatusRequest => 2,
+            Notify => 4,
+            Update => 5,
             Reserved(code) => code,
         }
     }
 }
 
+impl Display for OperationCode {
+    /// Presents the mnemonic used by `dig`, or `RESERVED<code>` when the code is unassigned.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use OperationCode::*;
+        match self {
+            StandardQuery => "QUERY".fmt(f),
+            InverseQuery => "IQUERY".fmt(f),
+            StatusRequest => "STATUS".fmt(f),
+            Notify => "NOTIFY".fmt(f),
+            Update => "UPDATE".fmt(f),
+            Reserved(code) => write!(f, "RESERVED{code}"),
+        }
+    }
+}
+
+/// The response codes of the [IANA registry], where codes above 15 are extended codes that
+/// require [EDNS(0)][super::Edns].
+///
+/// [IANA registry]: <https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-6>
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u8)]
-pub enum HeaderError {
+pub enum ResponseCode {
+    /// No error condition.
+    NoError,
+
     /// The name server was unable to interpret the query.
-    Format = 1,
+    Format,
 
     /// The name server was unable to process this query due to a problem with the name server.
     ServerFailure,
@@ -248,34 +341,185 @@ pub enum HeaderError {
     NotImplemented,
 
     /// The name server refuses to perform the specified operation for policy reasons.
-    Resfused,
+    Refused,
+
+    /// Some name that ought not to exist, does exist ([RFC2136]).
+    ///
+    /// [RFC2136]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2.2>
+    YxDomain,
+
+    /// Some RRset that ought not to exist, does exist ([RFC2136]).
+    ///
+    /// [RFC2136]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2.2>
+    YxRrSet,
+
+    /// Some RRset that ought to exist, does not exist ([RFC2136]).
+    ///
+    /// [RFC2136]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2.2>
+    NxRrSet,
+
+    /// The server is not authoritative for the zone named in the zone section ([RFC2136]), or the
+    /// request is not authorized ([RFC8945]).
+    ///
+    /// [RFC2136]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2.2>
+    /// [RFC8945]: <https://datatracker.ietf.org/doc/html/rfc8945>
+    NotAuth,
+
+    /// A name used in the prerequisite or update section is not within the zone denoted by the
+    /// zone section ([RFC2136]).
+    ///
+    /// [RFC2136]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2.2>
+    NotZone,
+
+    /// The DSO-TYPE of a DNS Stateful Operations message is not implemented ([RFC8490]).
+    ///
+    /// [RFC8490]: <https://datatracker.ietf.org/doc/html/rfc8490>
+    DsoTypeNotImplemented,
+
+    /// The EDNS version of the request is not supported ([RFC6891]), shared with the BADSIG code
+    /// of TSIG.
+    ///
+    /// [RFC6891]: <https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3>
+    BadVersion,
+
+    /// The TSIG key is not recognized.
+    BadKey,
+
+    /// The TSIG signature is outside of the time window.
+    BadTime,
+
+    /// The TKEY mode is bad.
+    BadMode,
+
+    /// The TKEY name is duplicated.
+    BadName,
+
+    /// The TKEY algorithm is not supported.
+    BadAlgorithm,
+
+    /// The TSIG MAC is too short.
+    BadTruncation,
+
+    /// The server cookie is bad or missing ([RFC7873]).
+    ///
+    /// [RFC7873]: <https://datatracker.ietf.org/doc/html/rfc7873>
+    BadCookie,
+
+    /// A code without a registered mnemonic.
+    Unknown(u16),
 }
 
-impl Display for HeaderError {
+impl ResponseCode {
+    /// The lower 4 bits of the code, which are carried by the header.
+    pub fn header_bits(self) -> u8 {
+        (u16::from(self) & 0b1111) as u8
+    }
+
+    /// The upper 8 bits of the code, which are carried by the OPT record.
+    pub fn extended_bits(self) -> u8 {
+        (u16::from(self) >> 4) as u8
+    }
+
+    /// Combines the lower 4 bits of the header with the upper 8 bits of the OPT record.
+    pub fn from_parts(header_bits: u8, extended_bits: u8) -> Self {
+        ((extended_bits as u16) << 4 | (header_bits & 0b1111) as u16).into()
+    }
+}
+
+impl From<u16> for ResponseCode {
+    fn from(value: u16) -> Self {
+        use ResponseCode::*;
+        match value {
+            0 => NoError,
+            1 => Format,
+            2 => ServerFailure,
+            3 => Name,
+            4 => NotImplemented,
+            5 => Refused,
+            6 => YxDomain,
+            7 => YxRrSet,
+            8 => NxRrSet,
+            9 => NotAuth,
+            10 => NotZone,
+            11 => DsoTypeNotImplemented,
+            16 => BadVersion,
+            17 => BadKey,
+            18 => BadTime,
+            19 => BadMode,
+            20 => BadName,
+            21 => BadAlgorithm,
+            22 => BadTruncation,
+            23 => BadCookie,
+            code => Unknown(code),
+        }
+    }
+}
+
+impl From<ResponseCode> for u16 {
+    fn from(value: ResponseCode) -> Self {
+        use ResponseCode::*;
+        match value {
+            NoError => 0,
+            Format => 1,
+            ServerFailure => 2,
+            Name => 3,
+            NotImplemented => 4,
+            Refused => 5,
+            YxDomain => 6,
+            YxRrSet => 7,
+            NxRrSet => 8,
+            NotAuth => 9,
+            NotZone => 10,
+            DsoTypeNotImplemented => 11,
+            BadVersion => 16,
+            BadKey => 17,
+            BadTime => 18,
+            BadMode => 19,
+            BadName => 20,
+            BadAlgorithm => 21,
+            BadTruncation => 22,
+            BadCookie => 23,
+            Unknown(code) => code,
+        }
+    }
+}
+
+impl Display for ResponseCode {
+    /// Presents the IANA mnemonic of the code, or `RCODE<code>` when it has none.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use HeaderError::*;
+        use ResponseCode::*;
         match self {
-            Format => "The name server was unable to interpret the query.".fmt(f),
-            ServerFailure => "The name server was unable to process this query due to a problem with the name server.".fmt(f),
-            Name => "The domain name referenced in the query does not exist.".fmt(f),
-            NotImplemented => "The name server does not support the request kind of query".fmt(f),
-            Resfused => "The name server refuses to perform the specified operation for policy reasons" .fmt(f),
+            NoError => "NOERROR".fmt(f),
+            Format => "FORMERR".fmt(f),
+            ServerFailure => "SERVFAIL".fmt(f),
+            Name => "NXDOMAIN".fmt(f),
+            NotImplemented => "NOTIMP".fmt(f),
+            Refused => "REFUSED".fmt(f),
+            YxDomain => "YXDOMAIN".fmt(f),
+            YxRrSet => "YXRRSET".fmt(f),
+            NxRrSet => "NXRRSET".fmt(f),
+            NotAuth => "NOTAUTH".fmt(f),
+            NotZone => "NOTZONE".fmt(f),
+            DsoTypeNotImplemented => "DSOTYPENI".fmt(f),
+            BadVersion => "BADVERS".fmt(f),
+            BadKey => "BADKEY".fmt(f),
+            BadTime => "BADTIME".fmt(f),
+            BadMode => "BADMODE".fmt(f),
+            BadName => "BADNAME".fmt(f),
+            BadAlgorithm => "BADALG".fmt(f),
+            BadTruncation => "BADTRUNC".fmt(f),
+            BadCookie => "BADCOOKIE".fmt(f),
+            Unknown(code) => write!(f, "RCODE{code}"),
         }
     }
 }
 
-impl Error for HeaderError {}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HeaderParseError {
     /// Parsing a header from a slice that isn't of size 12
     SliceSizeMismatch(usize),
-    /// Using a reserved operation code (i.e. in range `(3..15)`)
+    /// Using a reserved operation code (i.e. 3 or in range `(6..15)`)
     ReservedOperationCode(u8),
-    /// Using a reserved response code (i.e. in range `(6..15)`)
-    ReservedResponseCode(u8),
-    /// `Z` flag is not set to zore
-    ReservedZFlag(u8),
 }
 
 impl Display for HeaderParseError {
@@ -286,13 +530,7 @@ impl Display for HeaderParseError {
                 format!("a header must consist of 12 bytes, but found '{size}'").fmt(f)
             }
             ReservedOperationCode(code) => {
-                format!("codes in 3..15 are reserved for future use, but found '{code}'").fmt(f)
-            }
-            ReservedResponseCode(code) => {
-                format!("codes in 6..15 are reserved for future use, but found '{code}'").fmt(f)
-            }
-            ReservedZFlag(code) => {
-                format!("z flag in header must be set to 0, but found '{code}'").fmt(f)
+                format!("codes 3 and 6..15 are reserved for future use, but found '{code}'").fmt(f)
             }
         }
     }
@@ -304,9 +542,6 @@ impl TryFrom<[u8; 12]> for Header {
     type Error = HeaderParseError;
 
     fn try_from(value: [u8; 12]) -> Result<Self, Self::Error> {
-        use HeaderError::*;
-        use HeaderParseError::*;
-        use OperationCode::*;
         use PacketType::*;
 
         let mut buf = &value[..];
@@ -320,39 +555,18 @@ impl TryFrom<[u8; 12]> for Header {
             Query
         };
 
-        let operation_code = match ((flags & 0b0111_1000_0000_0000) >> 11) as u8 {
-            0 => StandardQuery,
-            1 => InverseQuery,
-            2 => StatusRequest,
-            code => {
-                eprintln!("using fucking reserved operation code");
-                Reserved(code as u16)
-                // return Err(ReservedOperationCode(code)),
-            }
-        };
+        let operation_code = OperationCode::from((flags & 0b0111_1000_0000_0000) >> 11);
 
         let authoritative_answer = (flags & 0b0000_0100_0000_0000) != 0;
         let truncated_message = (flags & 0b0000_0010_0000_0000) != 0;
         let recursion_desired = (flags & 0b0000_0001_0000_0000) != 0;
         let recursion_available = (flags & 0b0000_0000_1000_0000) != 0;
 
-        match ((flags & 0b0000_0000_0111_0000) >> 4) as u8 {
-            0 => (),
-            code => {
-                eprintln!("the 'z' flag was set to {code}, but should've remained as zero");
-                // return Err(ReservedZFlag(code)),
-            }
-        }
+        let reserved = (flags & 0b0000_0000_0100_0000) != 0;
+        let authentic_data = (flags & 0b0000_0000_0010_0000) != 0;
+        let checking_disabled = (flags & 0b0000_0000_0001_0000) != 0;
 
-        let response = match (flags & 0b0000_0000_0000_1111) as u8 {
-            0 => Ok(()),
-            1 => Err(Format),
-            2 => Err(ServerFailure),
-            3 => Err(Name),
-            4 => Err(NotImplemented),
-            5 => Err(Resfused),
-            code => return Err(ReservedResponseCode(code)),
-        };
+        let response = ResponseCode::from(flags & 0b0000_0000_0000_1111);
 
         let question_count = buf.get_u16();
         let answer_count = buf.get_u16();
@@ -367,6 +581,9 @@ impl TryFrom<[u8; 12]> for Header {
             truncated_message,
             recursion_desired,
             recursion_available,
+            reserved,
+            authentic_data,
+            checking_disabled,
             response,
             question_count,
             answer_count,
@@ -405,13 +622,12 @@ impl From<Header> for [u8; 12] {
             let tc = (header.truncated_message as u16) << 9;
             let rd = (header.recursion_desired as u16) << 8;
             let ra = (header.recursion_available as u16) << 7;
-            let z = 0;
-            let rcode = match header.response {
-                Ok(_) => 0,
-                Err(code) => code as u16,
-            };
+            let z = (header.reserved as u16) << 6;
+            let ad = (header.authentic_data as u16) << 5;
+            let cd = (header.checking_disabled as u16) << 4;
+            let rcode = header.response.header_bits() as u16;
 
-            qr | opcode | aa | tc | rd | ra | z | rcode
+            qr | opcode | aa | tc | rd | ra | z | ad | cd | rcode
         };
         buf.put_u16(flags);
 
@@ -424,6 +640,14 @@ impl From<Header> for [u8; 12] {
     }
 }
 
+impl Header {
+    /// Appends the wire format of the header to `buf`.
+    pub fn write(&self, buf: &mut impl Sink) {
+        let bytes: [u8; 12] = self.clone().into();
+        buf.put_slice(&bytes);
+    }
+}
+
 impl From<Header> for Vec<u8> {
     fn from(header: Header) -> Self {
         let buf: [u8; 12] = header.into();
@@ -447,7 +671,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -465,7 +692,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 2,
                 answer_count: 0,
                 authority_count: 0,
@@ -486,7 +716,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -504,7 +737,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -525,7 +761,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -543,7 +782,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -561,7 +803,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -579,7 +824,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -597,7 +845,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -615,7 +866,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: false,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -633,7 +887,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: false,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -651,7 +908,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: false,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -669,7 +929,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: false,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -690,7 +953,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -708,7 +974,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -729,7 +998,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -747,7 +1019,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -768,7 +1043,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -786,7 +1064,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -807,7 +1088,10 @@ mod parsing {
                 truncated_message: false,
                 recursion_desired: true,
                 recursion_available: false,
-                response: Ok(()),
+                reserved: false,
+                authentic_data: false,
+                checking_disabled: false,
+                response: ResponseCode::NoError,
                 question_count: 1,
                 answer_count: 0,
                 authority_count: 0,
@@ -816,3 +1100,86 @@ mod parsing {
         );
     }
 }
+
+#[cfg(test)]
+mod flags {
+    use super::*;
+
+    #[test]
+    fn round_trip_dnssec_bits() {
+        let buf = [0, 1, 0x81, 0x30, 0, 1, 0, 0, 0, 0, 0, 0];
+        let header = Header::try_from(buf).unwrap();
+        assert!(header.authentic_data);
+        assert!(header.checking_disabled);
+        assert!(!header.reserved);
+        assert_eq!(<[u8; 12]>::from(header), buf);
+
+        let header = HeaderBuilder::new()
+            .authentic_data(true)
+            .checking_disabled(false)
+            .build();
+        assert_eq!(<[u8; 12]>::from(header)[3], 0x20);
+    }
+
+    #[test]
+    fn preserve_reserved_bit() {
+        let buf = [0, 1, 0x01, 0x40, 0, 1, 0, 0, 0, 0, 0, 0];
+        let header = Header::try_from(buf).unwrap();
+        assert!(header.reserved);
+        assert!(!header.authentic_data && !header.checking_disabled);
+        assert_eq!(<[u8; 12]>::from(header), buf);
+    }
+}
+
+#[cfg(test)]
+mod operation_code {
+    use super::*;
+
+    #[test]
+    fn parse_notify_and_update() {
+        for (flags, code) in [
+            (0x20, OperationCode::Notify),
+            (0x28, OperationCode::Update),
+            (0x30, OperationCode::Reserved(6)),
+        ] {
+            let header = Header::try_from([0, 1, flags, 0, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+            assert_eq!(header.operation_code, code);
+            assert_eq!(<[u8; 12]>::from(header)[2], flags);
+        }
+    }
+}
+
+#[cfg(test)]
+mod response_code {
+    use super::*;
+
+    #[test]
+    fn parse_update_response_codes() {
+        for (code, response) in [
+            (6, ResponseCode::YxDomain),
+            (9, ResponseCode::NotAuth),
+            (12, ResponseCode::Unknown(12)),
+        ] {
+            let header = Header::try_from([0, 1, 0x80, code, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+            assert_eq!(header.response, response);
+            assert_eq!(<[u8; 12]>::from(header)[3], code);
+        }
+    }
+
+    #[test]
+    fn split_extended_codes() {
+        let code = ResponseCode::BadCookie;
+        assert_eq!((code.header_bits(), code.extended_bits()), (7, 1));
+        assert_eq!(ResponseCode::from_parts(7, 1), code);
+        assert_eq!(ResponseCode::from_parts(7, 0), ResponseCode::YxRrSet);
+    }
+
+    #[test]
+    fn present_iana_mnemonics() {
+        assert_eq!(ResponseCode::NoError.to_string(), "NOERROR");
+        assert_eq!(ResponseCode::Name.to_string(), "NXDOMAIN");
+        assert_eq!(ResponseCode::NotZone.to_string(), "NOTZONE");
+        assert_eq!(ResponseCode::BadVersion.to_string(), "BADVERS");
+        assert_eq!(ResponseCode::from(3841).to_string(), "RCODE3841");
+    }
+}
@@ -0,0 +1,903 @@
+//! The JSON representation of messages of [RFC8427], enabled by the `json` feature.
+//!
+//! Every object has a decoded form, whose members hold the fields of the message, and a raw form
+//! holding the wire format in hexadecimal:
+//!
+//! ```txt
+//! { "ID": 19678, "QR": 0, "Opcode": 0, "AA": 0, "TC": 0, "RD": 1, "RA": 0, "AD": 0, "CD": 0,
+//!   "RCODE": 0, "QDCOUNT": 1, "ANCOUNT": 0, "NSCOUNT": 0, "ARCOUNT": 0,
+//!   "questionRRs": [ { "NAME": "example.com.", "TYPE": 1, "TYPEname": "A", "CLASS": 1,
+//!                      "CLASSname": "IN" } ] }
+//!
+//! { "messageOctetsHEX": "4CDE0100000100000000000007..." }
+//! ```
+//!
+//! Both forms are read back, and members of either form may be mixed within an object.
+//!
+//! [RFC8427]: <https://datatracker.ietf.org/doc/html/rfc8427>
+use std::{
+    error::Error,
+    fmt::{self, Display, Write},
+};
+
+use super::{
+    encoding::{decode_hex, write_hex},
+    parse_label, split_tokens, Edns, EdnsError, EncodeError, Header, HeaderParseError, Label,
+    LabelError, Message, MessageParseError, PacketType, Question, QuestionClass, QuestionType,
+    ResourceClass, ResourceData, ResourceDataError, ResourceRecord, ResourceType, ResponseCode,
+};
+
+/// Objects nested deeper than this are rejected, so that parsing can't overflow the stack.
+const MAX_DEPTH: usize = 32;
+
+/// A JSON value, restricted to the integers used by RFC 8427.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => "null".fmt(f),
+            Value::Bool(value) => value.fmt(f),
+            Value::Number(value) => value.fmt(f),
+            Value::String(value) => write_string(f, value),
+            Value::Array(values) => {
+                "[".fmt(f)?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        ",".fmt(f)?;
+                    }
+                    value.fmt(f)?;
+                }
+                "]".fmt(f)
+            }
+            Value::Object(members) => {
+                "{".fmt(f)?;
+                for (index, (name, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        ",".fmt(f)?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{value}")?;
+                }
+                "}".fmt(f)
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => "\\\"".fmt(f)?,
+            '\\' => "\\\\".fmt(f)?,
+            '\n' => "\\n".fmt(f)?,
+            '\r' => "\\r".fmt(f)?,
+            '\t' => "\\t".fmt(f)?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// A recursive descent parser over the bytes of a JSON text.
+struct Parser<'a> {
+    text: &'a [u8],
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn parse(text: &str) -> Result<Value, JsonError> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            offset: 0,
+        };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        match parser.offset == parser.text.len() {
+            true => Ok(value),
+            false => Err(JsonError::Syntax(parser.offset)),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.text.get(self.offset) {
+            self.offset += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.get(self.offset).copied()
+    }
+
+    fn expect(&mut self, token: &[u8]) -> Result<(), JsonError> {
+        match self.text[self.offset..].starts_with(token) {
+            true => {
+                self.offset += token.len();
+                Ok(())
+            }
+            false => Err(JsonError::Syntax(self.offset)),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, JsonError> {
+        if depth > MAX_DEPTH {
+            return Err(JsonError::Syntax(self.offset));
+        }
+
+        match self.peek() {
+            Some(b'n') => self.expect(b"null").map(|_| Value::Null),
+            Some(b't') => self.expect(b"true").map(|_| Value::Bool(true)),
+            Some(b'f') => self.expect(b"false").map(|_| Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b'[') => {
+                self.offset += 1;
+                let mut values = vec![];
+                if self.peek() == Some(b']') {
+                    self.offset += 1;
+                    return Ok(Value::Array(values));
+                }
+                loop {
+                    values.push(self.value(depth + 1)?);
+                    match self.peek() {
+                        Some(b',') => self.offset += 1,
+                        Some(b']') => {
+                            self.offset += 1;
+                            return Ok(Value::Array(values));
+                        }
+                        _ => return Err(JsonError::Syntax(self.offset)),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.offset += 1;
+                let mut members = vec![];
+                if self.peek() == Some(b'}') {
+                    self.offset += 1;
+                    return Ok(Value::Object(members));
+                }
+                loop {
+                    if self.peek() != Some(b'"') {
+                        return Err(JsonError::Syntax(self.offset));
+                    }
+                    let name = self.string()?;
+                    if self.peek() != Some(b':') {
+                        return Err(JsonError::Syntax(self.offset));
+                    }
+                    self.offset += 1;
+                    members.push((name, self.value(depth + 1)?));
+                    match self.peek() {
+                        Some(b',') => self.offset += 1,
+                        Some(b'}') => {
+                            self.offset += 1;
+                            return Ok(Value::Object(members));
+                        }
+                        _ => return Err(JsonError::Syntax(self.offset)),
+                    }
+                }
+            }
+            _ => Err(JsonError::Syntax(self.offset)),
+        }
+    }
+
+    /// Only integers are read, as RFC 8427 has no use for fractions.
+    fn number(&mut self) -> Result<Value, JsonError> {
+        let start = self.offset;
+        if self.text[self.offset] == b'-' {
+            self.offset += 1;
+        }
+        while let Some(b'0'..=b'9') = self.text.get(self.offset) {
+            self.offset += 1;
+        }
+
+        std::str::from_utf8(&self.text[start..self.offset])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .map(Value::Number)
+            .ok_or(JsonError::Syntax(start))
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        let start = self.offset;
+        let syntax = || JsonError::Syntax(start);
+        self.offset += 1;
+
+        let mut bytes = vec![];
+        loop {
+            let c = *self.text.get(self.offset).ok_or_else(syntax)?;
+            self.offset += 1;
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    let escape = *self.text.get(self.offset).ok_or_else(syntax)?;
+                    self.offset += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let high = self.code_unit().ok_or_else(syntax)?;
+                            let code = match high {
+                                0xd800..=0xdbff => {
+                                    self.expect(b"\\u").map_err(|_| syntax())?;
+                                    match self.code_unit().ok_or_else(syntax)? {
+                                        low @ 0xdc00..=0xdfff => {
+                                            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                                        }
+                                        _ => return Err(syntax()),
+                                    }
+                                }
+                                // a low surrogate must follow a high one
+                                0xdc00..=0xdfff => return Err(syntax()),
+                                code => code,
+                            };
+                            char::from_u32(code).ok_or_else(syntax)?
+                        }
+                        _ => return Err(syntax()),
+                    };
+                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                0x00..=0x1f => return Err(syntax()),
+                c => bytes.push(c),
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| syntax())
+    }
+
+    fn code_unit(&mut self) -> Option<u32> {
+        let digits = self.text.get(self.offset..self.offset + 4)?;
+        if !digits.iter().all(u8::is_ascii_hexdigit) {
+            return None;
+        }
+        self.offset += 4;
+        u32::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()
+    }
+}
+
+/// The members of a JSON object, read by name.
+struct Object<'a>(&'a [(String, Value)]);
+
+impl<'a> Object<'a> {
+    fn parse(value: &'a Value) -> Result<Self, JsonError> {
+        match value {
+            Value::Object(members) => Ok(Object(members)),
+            _ => Err(JsonError::Member("object".to_owned())),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&'a Value> {
+        self.0
+            .iter()
+            .find(|(member, _)| member == name)
+            .map(|(_, value)| value)
+    }
+
+    fn integer<T: TryFrom<i64>>(&self, name: &str) -> Result<Option<T>, JsonError> {
+        match self.get(name) {
+            Some(Value::Number(value)) => T::try_from(*value)
+                .map(Some)
+                .map_err(|_| JsonError::Member(name.to_owned())),
+            Some(_) => Err(JsonError::Member(name.to_owned())),
+            None => Ok(None),
+        }
+    }
+
+    fn string(&self, name: &str) -> Result<Option<&'a str>, JsonError> {
+        match self.get(name) {
+            Some(Value::String(value)) => Ok(Some(value)),
+            Some(_) => Err(JsonError::Member(name.to_owned())),
+            None => Ok(None),
+        }
+    }
+
+    fn hex(&self, name: &str) -> Result<Option<Vec<u8>>, JsonError> {
+        match self.string(name)? {
+            Some(value) => decode_hex(value)
+                .map(Some)
+                .ok_or_else(|| JsonError::Member(name.to_owned())),
+            None => Ok(None),
+        }
+    }
+
+    /// Flags are given as `0` or `1` by RFC 8427, but booleans are accepted as well.
+    fn flag(&self, name: &str) -> Result<bool, JsonError> {
+        match self.get(name) {
+            Some(Value::Bool(value)) => Ok(*value),
+            Some(Value::Number(0)) | None => Ok(false),
+            Some(Value::Number(1)) => Ok(true),
+            Some(_) => Err(JsonError::Member(name.to_owned())),
+        }
+    }
+
+    fn array(&self, name: &str) -> Result<&'a [Value], JsonError> {
+        match self.get(name) {
+            Some(Value::Array(values)) => Ok(values),
+            Some(_) => Err(JsonError::Member(name.to_owned())),
+            None => Ok(&[]),
+        }
+    }
+
+    /// Reads a code from its `<name>` number or its `<name>name` mnemonic.
+    fn code<T: From<u16> + std::str::FromStr>(&self, name: &str) -> Result<T, JsonError> {
+        if let Some(code) = self.integer::<u16>(name)? {
+            return Ok(code.into());
+        }
+
+        let mnemonic = format!("{name}name");
+        match self.string(&mnemonic)? {
+            Some(value) => value.parse().map_err(|_| JsonError::Member(mnemonic)),
+            None => Err(JsonError::Member(name.to_owned())),
+        }
+    }
+
+    /// Reads a name from its `NAME` presentation format or its `NAMEHEX` wire format.
+    fn name(&self) -> Result<Label, JsonError> {
+        if let Some(name) = self.string("NAME")? {
+            return Ok(name.parse()?);
+        }
+
+        match self.hex("NAMEHEX")? {
+            Some(buf) => Ok(parse_label(&buf)?.0),
+            None => Err(JsonError::Member("NAME".to_owned())),
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> Value {
+    let mut text = String::new();
+    let _ = write_hex(&mut text, bytes);
+    Value::String(text.to_ascii_uppercase())
+}
+
+fn members(members: impl IntoIterator<Item = (&'static str, Value)>) -> Vec<(String, Value)> {
+    members
+        .into_iter()
+        .map(|(name, value)| (name.to_owned(), value))
+        .collect()
+}
+
+impl Header {
+    fn json_members(&self) -> Vec<(String, Value)> {
+        let flag = |value: bool| Value::Number(value as i64);
+        members([
+            ("ID", Value::Number(self.id.into())),
+            ("QR", flag(self.typ == PacketType::Response)),
+            (
+                "Opcode",
+                Value::Number(u16::from(self.operation_code).into()),
+            ),
+            ("AA", flag(self.authoritative_answer)),
+            ("TC", flag(self.truncated_message)),
+            ("RD", flag(self.recursion_desired)),
+            ("RA", flag(self.recursion_available)),
+            ("AD", flag(self.authentic_data)),
+            ("CD", flag(self.checking_disabled)),
+            ("RCODE", Value::Number(self.response.header_bits().into())),
+            ("QDCOUNT", Value::Number(self.question_count.into())),
+            ("ANCOUNT", Value::Number(self.answer_count.into())),
+            ("NSCOUNT", Value::Number(self.authority_count.into())),
+            ("ARCOUNT", Value::Number(self.addtional_count.into())),
+        ])
+    }
+
+    fn from_json_object(object: &Object) -> Result<Self, JsonError> {
+        if let Some(buf) = object.hex("headerOctetsHEX")? {
+            return Header::try_from(&buf[..]).map_err(JsonError::Header);
+        }
+
+        Ok(Header {
+            id: object.integer("ID")?.unwrap_or(0),
+            typ: match object.flag("QR")? {
+                true => PacketType::Response,
+                false => PacketType::Query,
+            },
+            operation_code: object.integer::<u16>("Opcode")?.unwrap_or(0).into(),
+            authoritative_answer: object.flag("AA")?,
+            truncated_message: object.flag("TC")?,
+            recursion_desired: object.flag("RD")?,
+            recursion_available: object.flag("RA")?,
+            reserved: false,
+            authentic_data: object.flag("AD")?,
+            checking_disabled: object.flag("CD")?,
+            // the upper bits of extended codes are carried by the OPT record
+            response: match object.integer::<u16>("RCODE")?.unwrap_or(0) {
+                code @ 0..=0b1111 => ResponseCode::from(code),
+                _ => return Err(JsonError::Member("RCODE".to_owned())),
+            },
+            question_count: object.integer("QDCOUNT")?.unwrap_or(0),
+            answer_count: object.integer("ANCOUNT")?.unwrap_or(0),
+            authority_count: object.integer("NSCOUNT")?.unwrap_or(0),
+            addtional_count: object.integer("ARCOUNT")?.unwrap_or(0),
+        })
+    }
+
+    /// The decoded JSON object of the header, see the [module documentation][self].
+    pub fn to_json(&self) -> String {
+        Value::Object(self.json_members()).to_string()
+    }
+
+    /// The `headerOctetsHEX` JSON object of the header.
+    pub fn to_json_hex(&self) -> String {
+        let buf: [u8; 12] = self.clone().into();
+        Value::Object(members([("headerOctetsHEX", hex(&buf))])).to_string()
+    }
+
+    /// Reads a header from either of its JSON objects, where missing fields are zero.
+    pub fn from_json(text: &str) -> Result<Self, JsonError> {
+        Header::from_json_object(&Object::parse(&Parser::parse(text)?)?)
+    }
+}
+
+impl Question {
+    fn json_members(&self) -> Vec<(String, Value)> {
+        members([
+            ("NAME", Value::String(self.name.to_string())),
+            ("TYPE", Value::Number(u16::from(self.typ).into())),
+            ("TYPEname", Value::String(self.typ.to_string())),
+            ("CLASS", Value::Number(u16::from(self.class).into())),
+            ("CLASSname", Value::String(self.class.to_string())),
+        ])
+    }
+
+    fn json_hex_members(&self) -> Vec<(String, Value)> {
+        members([
+            ("NAMEHEX", hex(&Vec::from(self.name.clone()))),
+            ("TYPE", Value::Number(u16::from(self.typ).into())),
+            ("CLASS", Value::Number(u16::from(self.class).into())),
+        ])
+    }
+
+    fn from_json_object(object: &Object) -> Result<Self, JsonError> {
+        Ok(Question {
+            name: object.name()?,
+            typ: object.code::<QuestionType>("TYPE")?,
+            class: object.code::<QuestionClass>("CLASS")?,
+        })
+    }
+
+    /// The decoded JSON object of the question, see the [module documentation][self].
+    pub fn to_json(&self) -> String {
+        Value::Object(self.json_members()).to_string()
+    }
+
+    /// The JSON object of the question, with its name in the `NAMEHEX` wire format.
+    pub fn to_json_hex(&self) -> String {
+        Value::Object(self.json_hex_members()).to_string()
+    }
+
+    /// Reads a question from either of its JSON objects.
+    pub fn from_json(text: &str) -> Result<Self, JsonError> {
+        Question::from_json_object(&Object::parse(&Parser::parse(text)?)?)
+    }
+}
+
+impl ResourceRecord {
+    fn json_members(&self) -> Result<Vec<(String, Value)>, ResourceDataError> {
+        let data = Vec::try_from(self.data.clone())?;
+        let mut object = members([
+            ("NAME", Value::String(self.name.to_string())),
+            ("TYPE", Value::Number(u16::from(self.typ()).into())),
+            ("TYPEname", Value::String(self.typ().to_string())),
+            ("CLASS", Value::Number(u16::from(self.class).into())),
+            ("CLASSname", Value::String(self.class.to_string())),
+            ("TTL", Value::Number(self.time_to_live.into())),
+            ("RDLENGTH", Value::Number(data.len() as i64)),
+            ("RDATAHEX", hex(&data)),
+        ]);
+
+        // the data of these types only has the generic presentation format
+        match self.data {
+            ResourceData::Null(_)
+            | ResourceData::Opt(_)
+            | ResourceData::Unknown { .. }
+            | ResourceData::Empty(_) => {}
+            _ => object.push((
+                format!("rdata{}", self.typ()),
+                Value::String(self.data.to_string()),
+            )),
+        }
+
+        Ok(object)
+    }
+
+    fn json_hex_members(&self) -> Result<Vec<(String, Value)>, ResourceDataError> {
+        let data = Vec::try_from(self.data.clone())?;
+        Ok(members([
+            ("NAMEHEX", hex(&Vec::from(self.name.clone()))),
+            ("TYPE", Value::Number(u16::from(self.typ()).into())),
+            ("CLASS", Value::Number(u16::from(self.class).into())),
+            ("TTL", Value::Number(self.time_to_live.into())),
+            ("RDLENGTH", Value::Number(data.len() as i64)),
+            ("RDATAHEX", hex(&data)),
+        ]))
+    }
+
+    fn from_json_object(object: &Object) -> Result<Self, JsonError> {
+        let typ = object.code::<ResourceType>("TYPE")?;
+        let class = object.code::<ResourceClass>("CLASS")?;
+        let rdata = format!("rdata{typ}");
+
+        let data = match (object.hex("RDATAHEX")?, object.string(&rdata)?) {
+            (Some(buf), _) => ResourceData::parse_in_class(typ, class, &buf)?,
+            (None, Some(text)) => {
+                let tokens = split_tokens(text).ok_or(JsonError::Member(rdata))?;
+                ResourceData::from_tokens(typ, &tokens, None)?
+            }
+            (None, None) => return Err(JsonError::Member("RDATAHEX".to_owned())),
+        };
+
+        Ok(ResourceRecord {
+            name: object.name()?,
+            class,
+            time_to_live: object
+                .integer("TTL")?
+                .ok_or_else(|| JsonError::Member("TTL".to_owned()))?,
+            data,
+        })
+    }
+
+    /// The decoded JSON object of the record, see the [module documentation][self].
+    ///
+    /// The data is given both in its `rdata<TYPE>` presentation format, when the type has one,
+    /// and in its `RDATAHEX` wire format, which fails when the data can't be written.
+    pub fn to_json(&self) -> Result<String, ResourceDataError> {
+        Ok(Value::Object(self.json_members()?).to_string())
+    }
+
+    /// The JSON object of the record, with its name and data in the `NAMEHEX` and `RDATAHEX` wire
+    /// formats.
+    pub fn to_json_hex(&self) -> Result<String, ResourceDataError> {
+        Ok(Value::Object(self.json_hex_members()?).to_string())
+    }
+
+    /// Reads a record from either of its JSON objects, where `RDATAHEX` is preferred over
+    /// `rdata<TYPE>`.
+    pub fn from_json(text: &str) -> Result<Self, JsonError> {
+        ResourceRecord::from_json_object(&Object::parse(&Parser::parse(text)?)?)
+    }
+}
+
+impl Message {
+    /// The decoded JSON object of the message, see the [module documentation][self].
+    ///
+    /// The [`edns`][Self::edns] pseudo record is found as an OPT record of the `additionalRRs`,
+    /// and empty sections are left out.
+    pub fn to_json(&self) -> Result<String, EncodeError> {
+        // the counts are those of the sections, as when the message is written
+        let [question_count, answer_count, authority_count, addtional_count] =
+            self.section_counts()?;
+        let header = Header {
+            question_count,
+            answer_count,
+            authority_count,
+            addtional_count,
+            ..self.header.clone()
+        };
+        let mut object = header.json_members();
+
+        let questions = self
+            .questions
+            .iter()
+            .map(|question| Value::Object(question.json_members()))
+            .collect::<Vec<_>>();
+        let records = |records: &mut dyn Iterator<Item = &ResourceRecord>| {
+            records
+                .map(|rr| rr.json_members().map(Value::Object))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let edns = self.edns.clone().map(ResourceRecord::from);
+
+        for (name, values) in [
+            ("questionRRs", questions),
+            ("answerRRs", records(&mut self.answers.iter())?),
+            ("authorityRRs", records(&mut self.authorities.iter())?),
+            (
+                "additionalRRs",
+                records(&mut self.additionals.iter().chain(edns.iter()))?,
+            ),
+        ] {
+            if !values.is_empty() {
+                object.push((name.to_owned(), Value::Array(values)));
+            }
+        }
+
+        Ok(Value::Object(object).to_string())
+    }
+
+    /// The `messageOctetsHEX` JSON object of the message.
+    pub fn to_json_hex(&self) -> Result<String, EncodeError> {
+        Ok(Value::Object(members([(
+            "messageOctetsHEX",
+            hex(&Vec::try_from(self.clone())?),
+        )]))
+        .to_string())
+    }
+
+    /// Reads a message from either of its JSON objects.
+    ///
+    /// The counts of the header are those of the sections, whatever their members say, and a
+    /// single question may be given by the `QNAME`, `QTYPE` and `QCLASS` members of the message.
+    pub fn from_json(text: &str) -> Result<Self, JsonError> {
+        let value = Parser::parse(text)?;
+        let object = Object::parse(&value)?;
+
+        if let Some(buf) = object.hex("messageOctetsHEX")? {
+            return Ok(Message::try_from(&buf[..])?);
+        }
+
+        let mut message = Message::new(0);
+        message.header = Header::from_json_object(&object)?;
+
+        let questions = object.array("questionRRs")?;
+        match object.get("QNAME") {
+            Some(_) if questions.is_empty() => {
+                let question = ["QNAME", "QTYPE", "QTYPEname", "QCLASS", "QCLASSname"]
+                    .iter()
+                    .filter_map(|name| Some((name[1..].to_owned(), object.get(name)?.clone())))
+                    .collect::<Vec<_>>();
+                message
+                    .questions
+                    .push(Question::from_json_object(&Object(&question))?);
+            }
+            _ => {
+                for question in questions {
+                    message
+                        .questions
+                        .push(Question::from_json_object(&Object::parse(question)?)?);
+                }
+            }
+        }
+
+        let records = |name| {
+            object
+                .array(name)?
+                .iter()
+                .map(|value| ResourceRecord::from_json_object(&Object::parse(value)?))
+                .collect::<Result<Vec<_>, JsonError>>()
+        };
+        message.answers = records("answerRRs")?;
+        message.authorities = records("authorityRRs")?;
+
+        for record in records("additionalRRs")? {
+            match record.typ() {
+                ResourceType::OPT if message.edns.is_some() => {
+                    return Err(MessageParseError::Edns(EdnsError::Duplicate).into())
+                }
+                ResourceType::OPT => {
+                    message.edns = Some(Edns::try_from(record).map_err(MessageParseError::Edns)?)
+                }
+                _ => message.additionals.push(record),
+            }
+        }
+
+        message.sync_counts();
+
+        Ok(message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonError {
+    /// The text isn't valid JSON, from the given byte offset on
+    Syntax(usize),
+    /// A member is missing, or its value doesn't have the expected type
+    Member(String),
+    Label(LabelError),
+    Data(ResourceDataError),
+    Header(HeaderParseError),
+    Message(MessageParseError),
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use JsonError::*;
+        match self {
+            Syntax(offset) => format!("invalid JSON at offset '{offset}'").fmt(f),
+            Member(name) => format!("member '{name}' is missing or invalid").fmt(f),
+            Label(err) => err.fmt(f),
+            Data(err) => err.fmt(f),
+            Header(err) => err.fmt(f),
+            Message(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for JsonError {}
+
+impl From<LabelError> for JsonError {
+    fn from(value: LabelError) -> Self {
+        Self::Label(value)
+    }
+}
+
+impl From<ResourceDataError> for JsonError {
+    fn from(value: ResourceDataError) -> Self {
+        Self::Data(value)
+    }
+}
+
+impl From<MessageParseError> for JsonError {
+    fn from(value: MessageParseError) -> Self {
+        Self::Message(value)
+    }
+}
+
+#[cfg(test)]
+mod rfc8427 {
+    use super::*;
+
+    fn message() -> Message {
+        let mut message = Message::new(0x4cde);
+        message.header.recursion_desired = true;
+        message
+            .ask("example.com", QuestionType::A, QuestionClass::IN)
+            .unwrap();
+        message.respond();
+        message.answer("example.com. 300 IN A 192.0.2.1".parse().unwrap());
+        message.add("ns.example.com. 60 IN TXT \"a \\\"b\\\"\"".parse().unwrap());
+        message.set_edns(Some(Edns::new(1232)));
+        message.sync_counts();
+        message
+    }
+
+    #[test]
+    fn header() {
+        let header = message().header;
+        assert_eq!(
+            header.to_json(),
+            concat!(
+                r#"{"ID":19678,"QR":1,"Opcode":0,"AA":0,"TC":0,"RD":1,"RA":0,"AD":0,"CD":0,"#,
+                r#""RCODE":0,"QDCOUNT":1,"ANCOUNT":1,"NSCOUNT":0,"ARCOUNT":2}"#
+            )
+        );
+        assert_eq!(Header::from_json(&header.to_json()), Ok(header.clone()));
+        assert_eq!(Header::from_json(&header.to_json_hex()), Ok(header));
+
+        let header = Header::from_json(r#"{ "ID": 1, "RD": true }"#).unwrap();
+        assert!(header.recursion_desired);
+        assert_eq!(header.typ, PacketType::Query);
+    }
+
+    #[test]
+    fn question() {
+        let question = message().questions.remove(0);
+        assert_eq!(
+            question.to_json(),
+            r#"{"NAME":"example.com.","TYPE":1,"TYPEname":"A","CLASS":1,"CLASSname":"IN"}"#
+        );
+        assert_eq!(
+            question.to_json_hex(),
+            r#"{"NAMEHEX":"076578616D706C6503636F6D00","TYPE":1,"CLASS":1}"#
+        );
+        assert_eq!(
+            Question::from_json(&question.to_json()),
+            Ok(question.clone())
+        );
+        assert_eq!(
+            Question::from_json(&question.to_json_hex()),
+            Ok(question.clone())
+        );
+        assert_eq!(
+            Question::from_json(r#"{"NAME":"example.com","TYPEname":"A","CLASSname":"IN"}"#),
+            Ok(question)
+        );
+    }
+
+    #[test]
+    fn record() {
+        let record = message().answers.remove(0);
+        assert_eq!(
+            record.to_json().unwrap(),
+            concat!(
+                r#"{"NAME":"example.com.","TYPE":1,"TYPEname":"A","CLASS":1,"CLASSname":"IN","#,
+                r#""TTL":300,"RDLENGTH":4,"RDATAHEX":"C0000201","rdataA":"192.0.2.1"}"#
+            )
+        );
+        assert_eq!(
+            ResourceRecord::from_json(&record.to_json().unwrap()),
+            Ok(record.clone())
+        );
+        assert_eq!(
+            ResourceRecord::from_json(&record.to_json_hex().unwrap()),
+            Ok(record.clone())
+        );
+        assert_eq!(
+            ResourceRecord::from_json(
+                r#"{"NAME":"example.com.","TYPEname":"A","CLASS":1,"TTL":300,"rdataA":"192.0.2.1"}"#
+            ),
+            Ok(record)
+        );
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let message = message();
+        let json = message.to_json().unwrap();
+        assert!(json.contains(r#""additionalRRs":["#));
+        assert!(json.contains(r#""TYPEname":"OPT""#));
+        assert!(!json.contains("authorityRRs"));
+        assert!(json.contains(r#""rdataTXT":"\"a \\\"b\\\"\"""#));
+
+        assert_eq!(Message::from_json(&json), Ok(message.clone()));
+        assert_eq!(
+            Message::from_json(&message.to_json_hex().unwrap()),
+            Ok(message)
+        );
+    }
+
+    #[test]
+    fn message_question_members() {
+        let message = Message::from_json(
+            r#"{ "ID": 32784, "QNAME": "example.com.", "QTYPE": 1, "QCLASSname": "IN" }"#,
+        )
+        .unwrap();
+        assert_eq!(message.header.question_count, 1);
+        assert_eq!(message.questions[0].name, "example.com".parse().unwrap());
+        assert_eq!(message.questions[0].typ, QuestionType::A);
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(Message::from_json(r#"{"ID": 1"#), Err(JsonError::Syntax(8)));
+        assert_eq!(
+            Message::from_json("[]"),
+            Err(JsonError::Member("object".to_owned()))
+        );
+        assert_eq!(
+            Header::from_json(r#"{"ID": 65536}"#),
+            Err(JsonError::Member("ID".to_owned()))
+        );
+        assert_eq!(
+            ResourceRecord::from_json(r#"{"NAME": "a.", "TYPE": 1, "CLASS": 1, "TTL": 1}"#),
+            Err(JsonError::Member("RDATAHEX".to_owned()))
+        );
+        assert_eq!(
+            Message::from_json(&"[".repeat(100)),
+            Err(JsonError::Syntax(33))
+        );
+        assert_eq!(
+            Question::from_json(r#"{"NAME": "😀.", "TYPE": 1, "CLASS": 1}"#)
+                .unwrap()
+                .name
+                .to_string(),
+            "\\240\\159\\152\\128."
+        );
+        assert_eq!(
+            Header::from_json(r#"{"ID": 1, "RCODE": 16}"#),
+            Err(JsonError::Member("RCODE".to_owned()))
+        );
+    }
+
+    #[test]
+    fn surrogates() {
+        let record = |text: &str| {
+            ResourceRecord::from_json(&format!(
+                r#"{{"NAME": "a.", "TYPE": 16, "CLASS": 1, "TTL": 1, "rdataTXT": "{text}"}}"#
+            ))
+        };
+        assert_eq!(
+            record(r#"\"\ud83d\ude00\""#).unwrap().data.to_string(),
+            "\"\\240\\159\\152\\128\""
+        );
+        assert_eq!(record(r#"\"\ud83d\u0041\""#), Err(JsonError::Syntax(61)));
+        assert_eq!(record(r#"\"\ude00\""#), Err(JsonError::Syntax(61)));
+        assert_eq!(record(r#"\"\u+041\""#), Err(JsonError::Syntax(61)));
+    }
+}
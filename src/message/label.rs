@@ -1,16 +1,46 @@
 use std::{
+    cmp::Ordering,
     error::Error,
     fmt::{self, Display},
+    hash::{Hash, Hasher},
+    str::FromStr,
 };
 
-use bytes::{Buf, BufMut};
+use super::{
+    encoding::{decode_escaped, write_quoted},
+    NameRef, Sink,
+};
+
+/// Labels are limited to 63 octets ([RFC1035 2.3.4]).
+///
+/// [RFC1035 2.3.4]: <https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4>
+pub const MAX_LABEL_LENGTH: usize = 63;
+
+/// Names are limited to 255 octets in their wire format ([RFC1035 2.3.4]).
+///
+/// [RFC1035 2.3.4]: <https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4>
+pub const MAX_NAME_LENGTH: usize = 255;
 
-/// An input sequence has a length greater than 255
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum LabelError {
+    /// A label is longer than [`MAX_LABEL_LENGTH`]
     MaxSizeReached(usize),
+    /// The wire format of a name is longer than [`MAX_NAME_LENGTH`]
+    NameTooLong(usize),
+    /// A name holds an empty label other than the root, e.g. `a..b`
+    EmptyLabel,
+    /// A `\` isn't followed by a character, or by three digits of a decimal octet
+    InvalidEscape(String),
     IncompleteBuffer,
     FalseEncodedLength(u8),
+    /// The two high bits of a length octet are `01` or `10`, which are reserved for future use
+    ReservedLabelType(u8),
+    /// A compression pointer refers to an offset outside of the message
+    InvalidPointer(u16),
+    /// A compression pointer refers back into the name it ends, which would never end
+    PointerLoop(u16),
+    /// A compression pointer refers to an offset after the labels it follows
+    ForwardPointer(u16),
 }
 
 impl Display for LabelError {
@@ -18,9 +48,14 @@ impl Display for LabelError {
         use LabelError::*;
         match self {
             MaxSizeReached(size) => {
-                format!("input sequences must be of length smaller than 256, buf found '{size}'")
+                format!("labels must be at most {MAX_LABEL_LENGTH} octets, but found '{size}'")
                     .fmt(f)
             }
+            NameTooLong(size) => {
+                format!("names must be at most {MAX_NAME_LENGTH} octets, but found '{size}'").fmt(f)
+            }
+            EmptyLabel => "only the root label may be empty".fmt(f),
+            InvalidEscape(name) => format!("invalid escape sequence in '{name}'").fmt(f),
             IncompleteBuffer => {
                 "input buffer is either incomplete or doens't end in a null byte".fmt(f)
             }
@@ -29,40 +64,278 @@ impl Display for LabelError {
                  input buffer"
             )
             .fmt(f),
+            ReservedLabelType(octet) => {
+                format!("the length octet '{octet:#010b}' uses a reserved label type").fmt(f)
+            }
+            InvalidPointer(offset) => {
+                format!("a compression pointer refers to '{offset}', outside of the message").fmt(f)
+            }
+            PointerLoop(offset) => {
+                format!("a compression pointer refers to '{offset}', forming a loop").fmt(f)
+            }
+            ForwardPointer(offset) => format!(
+                "a compression pointer refers to '{offset}', which isn't a prior occurrence"
+            )
+            .fmt(f),
         }
     }
 }
 
 impl Error for LabelError {}
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// A domain name, stored as the sequence of its labels without the root.
+///
+/// Names are compared and hashed case-insensitively ([RFC4343]), while their case is preserved.
+///
+/// [RFC4343]: <https://datatracker.ietf.org/doc/html/rfc4343>
+#[derive(Debug, Default, Clone)]
 pub struct Label(pub Vec<CharacterString>);
 
 impl Label {
-    /// Splits the input around b'.' to create a sequence of [`CharacterString`]s.
+    /// Splits the input around b'.' to create a sequence of [`CharacterString`]s, ignoring a
+    /// trailing b'.' of the root.
     pub fn parse(value: &[u8]) -> Result<Self, LabelError> {
-        let mut buf = vec![];
+        if value == b"." {
+            return Ok(Self::default());
+        }
+
+        let value = value.strip_suffix(b".").unwrap_or(value);
+        Self::checked(
+            value
+                .split(|&e| e == b'.')
+                .map(|string| CharacterString::String(string.to_owned()))
+                .collect(),
+        )
+    }
+
+    /// Parses a name in the presentation format, see [`Label::from_str`].
+    pub fn parse_str(value: &str) -> Result<Self, LabelError> {
+        value.parse()
+    }
+
+    /// Parses a name of a master file, which is relative to `origin` unless it ends with a `.`,
+    /// and stands for `origin` itself when it's `@` ([RFC1035 5.1]).
+    ///
+    /// [RFC1035 5.1]: <https://datatracker.ietf.org/doc/html/rfc1035#section-5.1>
+    pub fn parse_relative(value: &str, origin: &Label) -> Result<Self, LabelError> {
+        if value == "@" {
+            return Ok(origin.clone());
+        }
 
-        for string in value.split(|&e| e == b'.') {
-            match string.len() {
-                0 => return Err(LabelError::IncompleteBuffer),
-                length if length > 255 => return Err(LabelError::MaxSizeReached(length)),
-                _ => buf.push(CharacterString::String(string.to_owned())),
+        // the last dot is escaped when it's preceded by an odd number of backslashes
+        if let Some(rest) = value.strip_suffix('.') {
+            let slashes = rest.bytes().rev().take_while(|&c| c == b'\\').count();
+            if slashes % 2 == 0 {
+                return value.parse();
             }
         }
 
-        Ok(Self(buf))
+        let mut name: Label = value.parse()?;
+        name.0.extend_from_slice(&origin.0);
+        Self::checked(name.0)
     }
 
-    /// Splits the input around '.' to create a sequence of [`CharacterString`]s.
-    pub fn parse_str(value: &str) -> Result<Self, LabelError> {
-        Self::parse(value.as_bytes())
+    /// Enforces the limits of [RFC1035 2.3.4] on `labels`.
+    ///
+    /// [RFC1035 2.3.4]: <https://datatracker.ietf.org/doc/html/rfc1035#section-2.3.4>
+    fn checked(labels: Vec<CharacterString>) -> Result<Self, LabelError> {
+        for string in labels.iter() {
+            match string {
+                CharacterString::String(string) if string.is_empty() => {
+                    return Err(LabelError::EmptyLabel)
+                }
+                CharacterString::String(string) if string.len() > MAX_LABEL_LENGTH => {
+                    return Err(LabelError::MaxSizeReached(string.len()))
+                }
+                _ => (),
+            }
+        }
+
+        let label = Self(labels);
+        match label.encoded_len() {
+            length if length > MAX_NAME_LENGTH => Err(LabelError::NameTooLong(length)),
+            _ => Ok(label),
+        }
     }
 
     /// The number of domain sotred inside the label
     pub fn domain_count(&self) -> usize {
         self.0.len()
     }
+
+    /// Whether this is the root domain
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The length of the uncompressed wire format of the name
+    pub fn encoded_len(&self) -> usize {
+        self.0
+            .iter()
+            .map(|string| match string {
+                CharacterString::String(string) => string.len() + 1,
+                CharacterString::Compressed(_) => 1,
+            })
+            .sum::<usize>()
+            + 1
+    }
+
+    /// The labels of the name, from the leftmost one to the one preceding the root.
+    ///
+    /// A [`compression pointer`] isn't a label, and is skipped.
+    ///
+    /// [`compression pointer`]: CharacterString::Compressed
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.0.iter().filter_map(|string| match string {
+            CharacterString::String(string) => Some(string.as_slice()),
+            CharacterString::Compressed(_) => None,
+        })
+    }
+
+    /// The name without its leftmost label, or [`None`] for the root
+    pub fn parent(&self) -> Option<Label> {
+        self.0
+            .split_first()
+            .map(|(_, parent)| Self(parent.to_vec()))
+    }
+
+    /// Whether the name is `other` or one of its descendants, e.g. `www.example.com` is a
+    /// subdomain of `example.com` and of the root
+    pub fn is_subdomain_of(&self, other: &Label) -> bool {
+        match self.0.len().checked_sub(other.0.len()) {
+            Some(index) => self.0[index..]
+                .iter()
+                .zip(other.0.iter())
+                .all(|(a, b)| a.eq_ignore_ascii_case(b)),
+            None => false,
+        }
+    }
+
+    /// The name with every ASCII letter in lowercase, as used by the canonical form of
+    /// [RFC4034 6.2]
+    ///
+    /// [RFC4034 6.2]: <https://datatracker.ietf.org/doc/html/rfc4034#section-6.2>
+    pub fn to_lowercase(&self) -> Label {
+        Self(
+            self.0
+                .iter()
+                .map(|string| match string {
+                    CharacterString::String(string) => {
+                        CharacterString::String(string.to_ascii_lowercase())
+                    }
+                    compressed => compressed.clone(),
+                })
+                .collect(),
+        )
+    }
+}
+
+impl PartialEq for Label {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(other.0.iter())
+                .all(|(a, b)| a.eq_ignore_ascii_case(b))
+    }
+}
+
+impl Eq for Label {}
+
+impl PartialOrd for Label {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Label {
+    /// Orders names canonically ([RFC4034 6.1]), by their labels starting from the rightmost one,
+    /// where labels are compared as strings of lowercase octets.
+    ///
+    /// [RFC4034 6.1]: <https://datatracker.ietf.org/doc/html/rfc4034#section-6.1>
+    fn cmp(&self, other: &Self) -> Ordering {
+        use CharacterString::*;
+
+        for pair in self.0.iter().rev().zip(other.0.iter().rev()) {
+            let ordering = match pair {
+                (String(a), String(b)) => a
+                    .iter()
+                    .map(u8::to_ascii_lowercase)
+                    .cmp(b.iter().map(u8::to_ascii_lowercase)),
+                (Compressed(a), Compressed(b)) => a.cmp(b),
+                (String(_), Compressed(_)) => Ordering::Less,
+                (Compressed(_), String(_)) => Ordering::Greater,
+            };
+            if ordering.is_ne() {
+                return ordering;
+            }
+        }
+
+        self.0.len().cmp(&other.0.len())
+    }
+}
+
+impl Hash for Label {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.len().hash(state);
+        for string in self.0.iter() {
+            match string {
+                CharacterString::String(string) => {
+                    string.len().hash(state);
+                    string
+                        .iter()
+                        .for_each(|c| c.to_ascii_lowercase().hash(state));
+                }
+                CharacterString::Compressed(offset) => offset.hash(state),
+            }
+        }
+    }
+}
+
+impl FromStr for Label {
+    type Err = LabelError;
+
+    /// Parses a name in the presentation format of [RFC1035 5.1], where `\X` stands for the
+    /// character `X` and `\DDD` for the octet of decimal value `DDD`. The trailing `.` of the root
+    /// is optional, and `.` alone is the root itself.
+    ///
+    /// [RFC1035 5.1]: <https://datatracker.ietf.org/doc/html/rfc1035#section-5.1>
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "." {
+            return Ok(Self::default());
+        }
+
+        let decode = |label: &str| {
+            decode_escaped(label)
+                .map(CharacterString::String)
+                .ok_or_else(|| LabelError::InvalidEscape(s.to_owned()))
+        };
+        let mut labels = vec![];
+        let mut start = 0;
+        let mut escaped = false;
+
+        // labels are split at the dots that aren't escaped, and decoded on their own
+        for (index, c) in s.bytes().enumerate() {
+            match c {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'.' => {
+                    labels.push(decode(&s[start..index])?);
+                    start = index + 1;
+                }
+                _ => (),
+            }
+        }
+
+        // the last label is only empty after the trailing dot of the root
+        let last = &s[start..];
+        if !last.is_empty() || labels.is_empty() {
+            labels.push(decode(last)?);
+        }
+
+        Self::checked(labels)
+    }
 }
 
 pub fn parse_label(value: &[u8]) -> Result<(Label, usize), LabelError> {
@@ -72,20 +345,22 @@ pub fn parse_label(value: &[u8]) -> Result<(Label, usize), LabelError> {
     let mut offset = 0;
 
     loop {
-        if buf.is_empty() {
-            return Err(IncompleteBuffer);
-        }
-
-        match buf[0] {
-            0 => break,
+        match buf {
+            [] => return Err(IncompleteBuffer),
+            [0, ..] => break,
+            [octet, rest @ ..] if octet & 0b1100_0000 == 0b1100_0000 => {
+                let [low, ..] = rest else {
+                    return Err(IncompleteBuffer);
+                };
+                let pointer = u16::from_be_bytes([octet & 0b0011_1111, *low]);
+                labels.push(CharacterString::Compressed(pointer));
+                return Ok((Label(labels), offset + 2));
+            }
+            [octet, ..] if octet & 0b1100_0000 != 0 => return Err(ReservedLabelType(*octet)),
             _ => {
                 let (string, len) = parse_character_string(buf)?;
                 buf = &buf[len..];
                 offset += len;
-                if let CharacterString::Compressed(_) = string {
-                    labels.push(string);
-                    return Ok((Label(labels), offset));
-                }
                 labels.push(string);
             }
         }
@@ -94,6 +369,54 @@ pub fn parse_label(value: &[u8]) -> Result<(Label, usize), LabelError> {
     Ok((Label(labels), offset + 1))
 }
 
+/// The length of the wire format of the label at the start of `value`, which is checked in the
+/// same way as [`parse_label`] but never copied.
+pub fn skip_label(value: &[u8]) -> Result<usize, LabelError> {
+    use LabelError::*;
+    let mut offset = 0;
+
+    loop {
+        match &value[offset..] {
+            [] => return Err(IncompleteBuffer),
+            [0, ..] => return Ok(offset + 1),
+            [octet, rest @ ..] if octet & 0b1100_0000 == 0b1100_0000 => {
+                return match rest {
+                    [] => Err(IncompleteBuffer),
+                    _ => Ok(offset + 2),
+                }
+            }
+            [octet, ..] if octet & 0b1100_0000 != 0 => return Err(ReservedLabelType(*octet)),
+            [count, rest @ ..] => match rest.get(..*count as usize) {
+                Some(_) => offset += *count as usize + 1,
+                None => return Err(FalseEncodedLength(*count)),
+            },
+        }
+    }
+}
+
+/// Replaces a trailing [`compression pointer`] of `label` by the labels it refers to inside of
+/// `message`, which are read as a [`NameRef`].
+///
+/// [`compression pointer`]: CharacterString::Compressed
+pub fn expand_label(label: &mut Label, message: &[u8]) -> Result<(), LabelError> {
+    let Some(&CharacterString::Compressed(offset)) = label.0.last() else {
+        return Ok(());
+    };
+    if offset as usize >= message.len() {
+        return Err(LabelError::InvalidPointer(offset));
+    }
+
+    label.0.pop();
+    for string in NameRef::new(message, offset as usize).labels() {
+        label.0.push(CharacterString::String(string?.to_vec()));
+    }
+
+    match label.encoded_len() {
+        length if length > MAX_NAME_LENGTH => Err(LabelError::NameTooLong(length)),
+        _ => Ok(()),
+    }
+}
+
 impl TryFrom<&[u8]> for Label {
     type Error = LabelError;
 
@@ -103,23 +426,215 @@ impl TryFrom<&[u8]> for Label {
     }
 }
 
+impl Label {
+    /// Appends the wire format of the label to `buf`.
+    ///
+    /// When a [`LabelCompressor`] is given, `buf` must hold the whole message written so far, so
+    /// that the offsets it records are valid compression pointers.
+    pub fn write<'a>(&'a self, buf: &mut impl Sink, names: Option<&mut LabelCompressor<'a>>) {
+        match names {
+            Some(names) => names.write(self, buf),
+            None => self.write_uncompressed(buf),
+        }
+    }
+
+    fn write_uncompressed(&self, buf: &mut impl Sink) {
+        for string in self.0.iter() {
+            string.write(buf);
+        }
+
+        if let Some(CharacterString::Compressed(_)) = self.0.last() {
+            return;
+        }
+
+        buf.put_u8(0);
+    }
+}
+
+impl Label {
+    /// Presents the name relative to `origin` when it's one of its subdomains, see
+    /// [`RelativeName`].
+    pub fn relative_to<'a>(&'a self, origin: &'a Label) -> RelativeName<'a> {
+        RelativeName { name: self, origin }
+    }
+
+    /// Writes a single label, escaping the characters which would otherwise be read as part of
+    /// the master file syntax.
+    fn write_string(f: &mut fmt::Formatter<'_>, string: &CharacterString) -> fmt::Result {
+        match string {
+            CharacterString::String(string) => {
+                for &c in string.iter() {
+                    match c {
+                        b'.' | b'\\' | b'"' | b'(' | b')' | b';' | b'@' | b'$' => {
+                            write!(f, "\\{}", c as char)?
+                        }
+                        0x21..=0x7e => write!(f, "{}", c as char)?,
+                        _ => write!(f, "\\{c:03}")?,
+                    }
+                }
+                Ok(())
+            }
+            CharacterString::Compressed(offset) => write!(f, "[@{offset}]"),
+        }
+    }
+}
+
+impl Display for Label {
+    /// Presents the label as an absolute domain name, escaping the characters which would
+    /// otherwise be read as part of the master file syntax.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return ".".fmt(f);
+        }
+
+        for string in self.0.iter() {
+            Label::write_string(f, string)?;
+            if let CharacterString::String(_) = string {
+                ".".fmt(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A name presented relative to an origin, as in master files ([RFC1035 5.1]): `@` for the origin
+/// itself, the labels preceding the origin for its subdomains, and the absolute name otherwise.
+///
+/// Names are always absolute when the origin is the root.
+///
+/// [RFC1035 5.1]: <https://datatracker.ietf.org/doc/html/rfc1035#section-5.1>
+#[derive(Debug, Clone, Copy)]
+pub struct RelativeName<'a> {
+    name: &'a Label,
+    origin: &'a Label,
+}
+
+impl Display for RelativeName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.origin.is_root() || !self.name.is_subdomain_of(self.origin) {
+            return self.name.fmt(f);
+        }
+
+        match self.name.0.len() - self.origin.0.len() {
+            0 => "@".fmt(f),
+            count => {
+                for (index, string) in self.name.0[..count].iter().enumerate() {
+                    if index > 0 {
+                        ".".fmt(f)?;
+                    }
+                    Label::write_string(f, string)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 impl From<Label> for Vec<u8> {
     fn from(value: Label) -> Self {
         let mut buf = vec![];
+        value.write(&mut buf, None);
+        buf
+    }
+}
+
+/// Compression pointers can only address the first 16 KiB of a message.
+const MAX_POINTER_OFFSET: usize = 0b0011_1111_1111_1111;
+
+/// The number of suffixes a [`LabelCompressor`] remembers, past which names are still written
+/// but can't be pointed to.
+const MAX_SUFFIXES: usize = 128;
+
+/// Remembers the offsets of every domain name suffix written into a message, so that later
+/// occurrences of the same suffix are replaced by a pointer ([RFC1035 4.1.4]). The suffixes are
+/// borrowed from the names written, so that compressing a message doesn't allocate.
+///
+/// ```txt
+///     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+///     | 1  1|                OFFSET                   |
+///     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// [RFC1035 4.1.4]: <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4>
+#[derive(Debug, Clone)]
+pub struct LabelCompressor<'a> {
+    /// The suffixes written so far, along with their offsets from the start of the message
+    suffixes: [(&'a [CharacterString], u16); MAX_SUFFIXES],
+    count: usize,
+    /// The offset of the start of the message in the buffers written to
+    origin: usize,
+}
+
+impl Default for LabelCompressor<'_> {
+    fn default() -> Self {
+        Self::starting_at(0)
+    }
+}
+
+impl<'a> LabelCompressor<'a> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A compressor for a message starting after `origin` octets of the buffers written to, e.g.
+    /// after the length prefix of a TCP message.
+    pub fn starting_at(origin: usize) -> Self {
+        Self {
+            suffixes: [(&[], 0); MAX_SUFFIXES],
+            count: 0,
+            origin,
+        }
+    }
+
+    /// The number of suffixes remembered so far, to [`forget`][Self::forget] those remembered
+    /// after it, e.g. once a part of the message has been measured.
+    pub(super) fn mark(&self) -> usize {
+        self.count
+    }
+
+    /// Forgets the suffixes remembered after `mark` was taken.
+    pub(super) fn forget(&mut self, mark: usize) {
+        self.count = self.count.min(mark);
+    }
 
-        let last = value.0.last().cloned();
+    fn find(&self, suffix: &[CharacterString]) -> Option<u16> {
+        self.suffixes[..self.count]
+            .iter()
+            .find(|(known, _)| *known == suffix)
+            .map(|&(_, offset)| offset)
+    }
 
-        for string in value.0.into_iter() {
-            let bytes: Vec<u8> = string.into();
-            buf.extend(bytes);
+    /// Appends `label` to `buf`, which holds the message written so far, pointing to the longest
+    /// suffix that has already been written.
+    pub fn write(&mut self, label: &'a Label, buf: &mut impl Sink) {
+        // a label holding a pointer is not a complete name, thus it can't be looked up
+        if label
+            .0
+            .iter()
+            .any(|string| matches!(string, CharacterString::Compressed(_)))
+        {
+            return label.write_uncompressed(buf);
         }
 
-        if let Some(CharacterString::Compressed(_)) = last {
-            return buf;
+        for (index, string) in label.0.iter().enumerate() {
+            let suffix = &label.0[index..];
+
+            if let Some(offset) = self.find(suffix) {
+                CharacterString::Compressed(offset).write(buf);
+                return;
+            }
+
+            let offset = buf.offset() - self.origin;
+            if offset <= MAX_POINTER_OFFSET && self.count < MAX_SUFFIXES {
+                self.suffixes[self.count] = (suffix, offset as u16);
+                self.count += 1;
+            }
+
+            string.write(buf);
         }
 
         buf.put_u8(0);
-        buf
     }
 }
 
@@ -127,7 +642,7 @@ impl From<Label> for Vec<u8> {
 ///
 /// CharacterStrings are treated as binary information, and can be up to 256 characters in length
 /// (including the length octet)
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CharacterString {
     String(Vec<u8>),
     Compressed(u16),
@@ -149,36 +664,250 @@ impl TryFrom<&[u8]> for CharacterString {
     }
 }
 
+/// Reads a single length octet followed by that number of characters.
+///
+/// Compression pointers are only meaningful inside of domain names, so they're handled by
+/// [`parse_label`] and never returned by this function.
 pub fn parse_character_string(value: &[u8]) -> Result<(CharacterString, usize), LabelError> {
-    use CharacterString::*;
     use LabelError::*;
-    let mut buf = value;
-    Ok(match value.len() {
-        0 => return Err(IncompleteBuffer),
-        length if length > 255 => return Err(MaxSizeReached(length)),
-        length => match value[0] as usize {
-            count if (count & 0b1100_0000) >> 6 == 3 => {
-                let offset = buf.get_u16() ^ 0b1100_0000_0000_0000;
-                (Compressed(offset), 2)
-            }
-            count if count < length => (String(value[1..count + 1].to_owned()), count + 1),
-            count => return Err(FalseEncodedLength(count as u8)),
+    match value {
+        [] => Err(IncompleteBuffer),
+        [count, rest @ ..] => match rest.get(..*count as usize) {
+            Some(string) => Ok((
+                CharacterString::String(string.to_owned()),
+                *count as usize + 1,
+            )),
+            None => Err(FalseEncodedLength(*count)),
         },
-    })
+    }
 }
 
-impl From<CharacterString> for Vec<u8> {
-    fn from(value: CharacterString) -> Self {
-        use CharacterString::*;
-        let mut buf = vec![];
+impl CharacterString {
+    /// Compares the strings ignoring the case of ASCII letters.
+    pub fn eq_ignore_ascii_case(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::String(a), Self::String(b)) => a.eq_ignore_ascii_case(b),
+            (a, b) => a == b,
+        }
+    }
 
-        match value {
+    /// Appends the wire format of the string to `buf`.
+    pub fn write(&self, buf: &mut impl Sink) {
+        use CharacterString::*;
+        match self {
             String(string) => {
                 buf.put_u8(string.len() as u8);
-                buf.extend(string);
+                buf.put_slice(string);
             }
             Compressed(offset) => buf.put_u16(offset | 0b1100_0000_0000_0000),
         }
+    }
+}
+
+impl Display for CharacterString {
+    /// Presents the string between double quotes, escaping non-printable characters as `\DDD`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CharacterString::String(string) => write_quoted(f, string),
+            CharacterString::Compressed(offset) => write!(f, "[@{offset}]"),
+        }
+    }
+}
+
+impl From<CharacterString> for Vec<u8> {
+    fn from(value: CharacterString) -> Self {
+        let mut buf = vec![];
+        value.write(&mut buf);
         buf
     }
 }
+
+#[cfg(test)]
+mod domain_name {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn name(value: &str) -> Label {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn presentation_format() {
+        assert_eq!(name("."), Label::default());
+        assert_eq!(name("example.com."), name("example.com"));
+        assert_eq!(name("a\\.b.com").domain_count(), 2);
+        assert_eq!(name("a\\.b.com").iter().next(), Some(&b"a.b"[..]));
+        assert_eq!(name("\\065\\098c").iter().next(), Some(&b"Abc"[..]));
+
+        let escaped = name("a\\.b\\032\\255.example.");
+        assert_eq!(name(&escaped.to_string()), escaped);
+
+        assert_eq!("".parse::<Label>(), Err(LabelError::EmptyLabel));
+        assert_eq!("a..b".parse::<Label>(), Err(LabelError::EmptyLabel));
+        assert!(matches!(
+            "a\\25".parse::<Label>(),
+            Err(LabelError::InvalidEscape(_))
+        ));
+        assert!(matches!(
+            "a\\256".parse::<Label>(),
+            Err(LabelError::InvalidEscape(_))
+        ));
+    }
+
+    #[test]
+    fn size_limits() {
+        let label = "a".repeat(MAX_LABEL_LENGTH);
+        assert!(label.parse::<Label>().is_ok());
+        assert_eq!(
+            format!("{label}a").parse::<Label>(),
+            Err(LabelError::MaxSizeReached(64))
+        );
+
+        // 4 labels of 63 octets take 4 * 64 + 1 octets in the wire format
+        let long = [label.as_str(); 4].join(".");
+        assert_eq!(long.parse::<Label>(), Err(LabelError::NameTooLong(257)));
+        let longest = [label.as_str(), &label, &label, &"a".repeat(61)].join(".");
+        assert_eq!(longest.parse::<Label>().unwrap().encoded_len(), 255);
+    }
+
+    #[test]
+    fn case_insensitivity() {
+        assert_eq!(name("WWW.Example.COM"), name("www.example.com"));
+        assert_ne!(name("www.example.com"), name("www.example.org"));
+
+        let names: HashSet<Label> = [name("Example.com"), name("EXAMPLE.COM")].into();
+        assert_eq!(names.len(), 1);
+
+        // the case is kept for display
+        assert_eq!(name("Example.COM").to_string(), "Example.COM.");
+        assert_eq!(
+            name("Example.COM").to_lowercase().to_string(),
+            "example.com."
+        );
+    }
+
+    #[test]
+    fn expand_pointers() {
+        #[rustfmt::skip]
+        let message = [
+            3, b'c', b'o', b'm', 0,
+            7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0xc0, 0,
+            0xc0, 15,
+        ];
+
+        let mut label = Label(vec![CharacterString::Compressed(5)]);
+        expand_label(&mut label, &message).unwrap();
+        assert_eq!(label, name("example.com"));
+
+        let mut label = Label(vec![CharacterString::Compressed(15)]);
+        assert_eq!(
+            expand_label(&mut label, &message),
+            Err(LabelError::PointerLoop(15))
+        );
+
+        let mut label = Label(vec![CharacterString::Compressed(17)]);
+        assert_eq!(
+            expand_label(&mut label, &message),
+            Err(LabelError::InvalidPointer(17))
+        );
+
+        let mut label = Label(vec![CharacterString::String(vec![b'a'; 63]); 4]);
+        label.0.push(CharacterString::Compressed(5));
+        assert_eq!(
+            expand_label(&mut label, &message),
+            Err(LabelError::NameTooLong(269))
+        );
+    }
+
+    #[test]
+    fn hierarchy() {
+        let www = name("www.Example.com");
+        assert!(www.is_subdomain_of(&name("example.COM")));
+        assert!(www.is_subdomain_of(&www));
+        assert!(www.is_subdomain_of(&Label::default()));
+        assert!(!www.is_subdomain_of(&name("ample.com")));
+        assert!(!name("com").is_subdomain_of(&www));
+
+        assert_eq!(www.parent(), Some(name("example.com")));
+        assert_eq!(name("com").parent(), Some(Label::default()));
+        assert_eq!(Label::default().parent(), None);
+        assert!(Label::default().is_root());
+    }
+
+    #[test]
+    fn canonical_order() {
+        let mut names = [
+            "*.z.example",
+            "a.example",
+            "Z.a.example",
+            "example",
+            "\\001.z.example",
+            "yljkjljk.a.example",
+            "zABC.a.EXAMPLE",
+            "z.example",
+            "\\200.z.example",
+        ]
+        .map(name);
+        names.sort();
+        assert_eq!(
+            names.map(|name| name.to_string()),
+            [
+                "example.",
+                "a.example.",
+                "yljkjljk.a.example.",
+                "Z.a.example.",
+                "zABC.a.EXAMPLE.",
+                "z.example.",
+                "\\001.z.example.",
+                "*.z.example.",
+                "\\200.z.example.",
+            ]
+        );
+    }
+
+    #[test]
+    fn relative_names() {
+        let origin = name("example.com");
+        assert_eq!(name("Example.COM.").relative_to(&origin).to_string(), "@");
+        assert_eq!(
+            name("a\\.b.www.example.com")
+                .relative_to(&origin)
+                .to_string(),
+            "a\\.b.www"
+        );
+        assert_eq!(
+            name("example.org").relative_to(&origin).to_string(),
+            "example.org."
+        );
+        assert_eq!(
+            name("www.example.com")
+                .relative_to(&Label::default())
+                .to_string(),
+            "www.example.com."
+        );
+
+        for value in ["@", "a\\.b.www", "example.org."] {
+            let relative = Label::parse_relative(value, &origin).unwrap();
+            assert_eq!(relative.relative_to(&origin).to_string(), value);
+        }
+
+        // only the last dot ends an absolute name, unless it's escaped
+        assert_eq!(
+            Label::parse_relative("a\\..", &origin),
+            Ok(Label(vec![CharacterString::String(b"a.".to_vec())]))
+        );
+        assert_eq!(
+            Label::parse_relative("a\\.", &origin).map(|name| name.to_string()),
+            Ok("a\\..example.com.".to_owned())
+        );
+        assert_eq!(
+            Label::parse_relative("a\\\\.", &origin).map(|name| name.to_string()),
+            Ok("a\\\\.".to_owned())
+        );
+        assert_eq!(
+            Label::parse_relative("a..", &origin),
+            Err(LabelError::EmptyLabel)
+        );
+    }
+}
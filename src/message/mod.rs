@@ -40,22 +40,36 @@
 //! [`QNAME`]: question::Question::name
 //! [`QTYPE`]: question::Question::typ
 //! [`QCLASS`]: question::Question::class
+pub mod borrowed;
+pub mod edns;
+pub mod encoding;
 pub mod header;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod label;
 pub mod question;
 pub mod resource;
 pub mod type_class;
+pub mod update;
+pub mod wire;
 
 use std::{
+    borrow::Cow,
     error::Error,
     fmt::{self, Display},
 };
 
+pub use borrowed::*;
+pub use edns::*;
 pub use header::*;
+#[cfg(feature = "json")]
+pub use json::*;
 pub use label::*;
 pub use question::*;
 pub use resource::*;
 pub use type_class::*;
+pub use update::*;
+pub use wire::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Message {
@@ -64,6 +78,10 @@ pub struct Message {
     pub answers: Vec<ResourceRecord>,
     pub authorities: Vec<ResourceRecord>,
     pub additionals: Vec<ResourceRecord>,
+
+    /// The EDNS(0) pseudo record of the additional section, kept apart from the
+    /// [`additionals`][Self::additionals] as it doesn't hold any DNS data.
+    pub edns: Option<Edns>,
 }
 
 impl Message {
@@ -75,6 +93,7 @@ impl Message {
             answers: vec![],
             authorities: vec![],
             additionals: vec![],
+            edns: None,
         }
     }
 
@@ -84,8 +103,13 @@ impl Message {
     }
 
     /// Set the message type to [`response`][PacketType::Response]
+    ///
+    /// The `AD` bit of the query is cleared, as the data of the response isn't validated, and so
+    /// is the reserved `Z` bit.
     pub fn respond(&mut self) {
         self.header.typ = PacketType::Response;
+        self.header.authentic_data = false;
+        self.header.reserved = false;
     }
 
     /// Add a [`Question`] to the message [`questions`][Self::questions]
@@ -97,8 +121,6 @@ impl Message {
         typ: QuestionType,
         class: QuestionClass,
     ) -> Result<(), LabelError> {
-        self.header.question_count += 1;
-
         let name = Label::parse_str(name)?;
         self.questions.push(Question { name, typ, class });
         Ok(())
@@ -106,50 +128,415 @@ impl Message {
 
     /// Add a [`ResourceRecord`] to the message [`answers`][Self::answers]
     pub fn answer(&mut self, rr: ResourceRecord) {
-        self.header.answer_count += 1;
         self.answers.push(rr);
     }
 
     /// Add a [`ResourceRecord`] to the message [`authorities`][Self::authorities]
     pub fn authorize(&mut self, rr: ResourceRecord) {
-        self.header.authority_count += 1;
         self.authorities.push(rr);
     }
 
     /// Add a [`ResourceRecord`] to the message [`additionals`][Self::additionals]
     pub fn add(&mut self, rr: ResourceRecord) {
-        self.header.addtional_count += 1;
         self.additionals.push(rr);
     }
+
+    /// Set (or remove) the [`edns`][Self::edns] pseudo record of the message
+    pub fn set_edns(&mut self, edns: Option<Edns>) {
+        self.edns = edns;
+    }
+
+    /// The number of entries of the question, answer, authority and additional sections, the
+    /// latter including the pseudo record written with the message, see
+    /// [`written_edns`][Self::written_edns]
+    fn section_lengths(&self) -> [usize; 4] {
+        let edns = self.edns.is_some() || self.header.response.extended_bits() != 0;
+        [
+            self.questions.len(),
+            self.answers.len(),
+            self.authorities.len(),
+            self.additionals.len() + edns as usize,
+        ]
+    }
+
+    /// The pseudo record written with the message, which carries the upper bits of an extended
+    /// code set on the header directly, the way [`set_response_code`][Self::set_response_code]
+    /// would have stored it
+    fn written_edns(&self) -> Option<Cow<'_, Edns>> {
+        match (&self.edns, self.header.response.extended_bits()) {
+            (edns, 0) => edns.as_ref().map(Cow::Borrowed),
+            (Some(edns), extended_rcode) => Some(Cow::Owned(Edns {
+                extended_rcode,
+                ..edns.clone()
+            })),
+            (None, extended_rcode) => Some(Cow::Owned(Edns {
+                extended_rcode,
+                ..Edns::new(DEFAULT_UDP_PAYLOAD_SIZE)
+            })),
+        }
+    }
+
+    /// The header counts of the sections, failing on the first section that holds more entries
+    /// than a count can tell
+    fn section_counts(&self) -> Result<[u16; 4], EncodeError> {
+        let mut counts = [0; 4];
+        for ((count, entries), section) in
+            counts.iter_mut().zip(self.section_lengths()).zip(SECTIONS)
+        {
+            *count = u16::try_from(entries)
+                .map_err(|_| EncodeError::TooManyEntries { section, entries })?;
+        }
+
+        Ok(counts)
+    }
+
+    /// Set the counts of the header to the number of entries of each section, as far as they
+    /// fit, see [`validate_counts`][Self::validate_counts].
+    ///
+    /// The counts are otherwise only derived from the sections when the message is written, so
+    /// that they can't get out of step with them.
+    pub fn sync_counts(&mut self) {
+        let [questions, answers, authorities, additionals] = self
+            .section_lengths()
+            .map(|entries| u16::try_from(entries).unwrap_or(u16::MAX));
+        self.header.question_count = questions;
+        self.header.answer_count = answers;
+        self.header.authority_count = authorities;
+        self.header.addtional_count = additionals;
+    }
+
+    /// Check that the counts of the header match the number of entries of each section, e.g.
+    /// after the sections of a parsed message were modified directly, or when a section holds
+    /// more than `u16::MAX` entries
+    pub fn validate_counts(&self) -> Result<(), CountMismatch> {
+        let header = &self.header;
+        let counts = [
+            header.question_count,
+            header.answer_count,
+            header.authority_count,
+            header.addtional_count,
+        ];
+
+        for ((section, count), entries) in
+            SECTIONS.into_iter().zip(counts).zip(self.section_lengths())
+        {
+            if count as usize != entries {
+                return Err(CountMismatch {
+                    section,
+                    count,
+                    entries,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Encode the message within `max` octets, see [`truncate_to`][Self::truncate_to].
+    pub fn to_wire_with_limit(&self, max: usize) -> Result<Vec<u8>, EncodeError> {
+        let mut message = self.clone();
+        message.truncate_to(max)?;
+        message.try_into()
+    }
+
+    /// Drop whole RRsets from the end of the additional, then authority, then answer sections
+    /// until the message fits within `max` octets, e.g. before [`encode`][Encode::encode]-ing it
+    /// into a buffer of that size.
+    ///
+    /// The [`truncated_message`][Header::truncated_message] flag is only set when an answer or
+    /// authority RRset is dropped, since the additional records aren't needed to use the others
+    /// ([RFC2181 9]). The header, the questions and the [`edns`][Self::edns] pseudo record are
+    /// always kept, so the message only exceeds `max` when they don't fit on their own.
+    ///
+    /// [RFC2181 9]: <https://datatracker.ietf.org/doc/html/rfc2181#section-9>
+    pub fn truncate_to(&mut self, max: usize) -> Result<(), EncodeError> {
+        // dropping records only lengthens the others when their names were compressed against
+        // the dropped ones, so a second pass seldom drops anything
+        while self.truncate(max)? {}
+        Ok(())
+    }
+
+    /// Drops the RRsets that end past `max` octets, and those sharing a section with them,
+    /// returning whether any was dropped.
+    fn truncate(&mut self, max: usize) -> Result<bool, EncodeError> {
+        let mut ends = vec![];
+        let mut length = Counter::new();
+        self.write_sections(&mut length, |end| ends.push(end))?;
+        if length.offset() <= max {
+            return Ok(false);
+        }
+
+        // the pseudo record is written after all of the others
+        let edns = length.offset() - ends[ends.len() - 1];
+        let mut kept = ends
+            .iter()
+            .rposition(|end| end + edns <= max)
+            .unwrap_or_default();
+
+        let mut dropped_any = false;
+        let sections = [
+            &mut self.answers,
+            &mut self.authorities,
+            &mut self.additionals,
+        ];
+        for (index, records) in sections.into_iter().enumerate() {
+            let keep = kept.min(records.len());
+            kept -= keep;
+            if keep == records.len() {
+                continue;
+            }
+
+            let dropped = records.split_off(keep);
+            records.retain(|rr| {
+                !dropped.iter().any(|other| {
+                    rr.name == other.name && rr.class == other.class && rr.typ() == other.typ()
+                })
+            });
+            if index < 2 {
+                self.header.truncated_message = true;
+            }
+            dropped_any = true;
+        }
+
+        Ok(dropped_any)
+    }
+
+    /// The size of the largest UDP payload that may be sent to the requestor of this message
+    pub fn max_payload_size(&self) -> u16 {
+        self.edns
+            .as_ref()
+            .map_or(DEFAULT_UDP_PAYLOAD_SIZE, Edns::max_payload_size)
+    }
+
+    /// The response code of the message, combining the header with the extended bits of the
+    /// [`edns`][Self::edns] pseudo record
+    pub fn response_code(&self) -> ResponseCode {
+        // an extended code may have been set on the header directly
+        if self.header.response.extended_bits() != 0 {
+            return self.header.response;
+        }
+
+        let extended_bits = self.edns.as_ref().map_or(0, |edns| edns.extended_rcode);
+        ResponseCode::from_parts(self.header.response.header_bits(), extended_bits)
+    }
+
+    /// Set the response code of the message, adding an [`edns`][Self::edns] pseudo record when
+    /// an extended code doesn't fit in the header
+    pub fn set_response_code(&mut self, code: ResponseCode) {
+        self.header.response = ResponseCode::from(code.header_bits() as u16);
+        match (&mut self.edns, code.extended_bits()) {
+            (Some(edns), extended_rcode) => edns.extended_rcode = extended_rcode,
+            (None, 0) => (),
+            (None, extended_rcode) => self.set_edns(Some(Edns {
+                extended_rcode,
+                ..Edns::new(DEFAULT_UDP_PAYLOAD_SIZE)
+            })),
+        }
+    }
+}
+
+impl Message {
+    /// Appends the wire format of the message to `buf`, which may already hold e.g. the length
+    /// prefix of a TCP message.
+    ///
+    /// Domain names are compressed as described in [RFC1035 4.1.4], with pointers relative to the
+    /// start of the message, and the counts of the header are those of the sections, whatever the
+    /// header holds.
+    ///
+    /// Fails when a section holds more entries than a count can tell, or a record can't be
+    /// written, leaving `buf` holding the beginning of the message.
+    ///
+    /// [RFC1035 4.1.4]: <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4>
+    pub fn write(&self, buf: &mut impl Sink) -> Result<(), EncodeError> {
+        self.write_sections(buf, |_| ())
+    }
+
+    /// Writes the message, passing the offset at the end of the questions, then at the end of
+    /// every answer, authority and additional record to `record_end`.
+    fn write_sections(
+        &self,
+        buf: &mut impl Sink,
+        mut record_end: impl FnMut(usize),
+    ) -> Result<(), EncodeError> {
+        let mut names = LabelCompressor::starting_at(buf.offset());
+
+        let [questions, answers, authorities, additionals] = self.section_counts()?;
+        let header = Header {
+            question_count: questions,
+            answer_count: answers,
+            authority_count: authorities,
+            addtional_count: additionals,
+            ..self.header.clone()
+        };
+        header.write(buf);
+
+        for question in self.questions.iter() {
+            question.write(buf, Some(&mut names));
+        }
+        record_end(buf.offset());
+
+        for record in self
+            .answers
+            .iter()
+            .chain(self.authorities.iter())
+            .chain(self.additionals.iter())
+        {
+            record.write(buf, Some(&mut names))?;
+            record_end(buf.offset());
+        }
+
+        if let Some(edns) = self.written_edns() {
+            edns.write(buf)?;
+        }
+
+        Ok(())
+    }
 }
 
-impl From<Message> for Vec<u8> {
-    fn from(value: Message) -> Self {
+impl TryFrom<Message> for Vec<u8> {
+    type Error = EncodeError;
+
+    fn try_from(value: Message) -> Result<Self, Self::Error> {
         let mut buf = vec![];
+        value.write(&mut buf)?;
+        Ok(buf)
+    }
+}
 
-        let header: [u8; 12] = value.header.into();
-        buf.extend_from_slice(&header);
+impl Display for Message {
+    /// Presents the message the way `dig` does, e.g.
+    ///
+    /// ```txt
+    /// ;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 1234
+    /// ;; flags: qr rd; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 0
+    ///
+    /// ;; QUESTION SECTION:
+    /// ;codecrafters.io. IN A
+    ///
+    /// ;; ANSWER SECTION:
+    /// codecrafters.io. 60 IN A 8.8.8.8
+    /// ```
+    ///
+    /// The [`edns`][Self::edns] pseudo record is presented in its own `OPT PSEUDOSECTION`, and
+    /// empty sections are left out.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = &self.header;
+        writeln!(
+            f,
+            ";; ->>HEADER<<- opcode: {}, status: {}, id: {}",
+            header.operation_code,
+            self.response_code(),
+            header.id
+        )?;
 
-        for question in value.questions.into_iter() {
-            buf.extend::<Vec<_>>(question.into());
+        ";; flags:".fmt(f)?;
+        for (flag, set) in [
+            ("qr", header.typ == PacketType::Response),
+            ("aa", header.authoritative_answer),
+            ("tc", header.truncated_message),
+            ("rd", header.recursion_desired),
+            ("ra", header.recursion_available),
+            ("ad", header.authentic_data),
+            ("cd", header.checking_disabled),
+        ] {
+            if set {
+                write!(f, " {flag}")?;
+            }
         }
+        let [questions, answers, authorities, additionals] = self.section_lengths();
+        writeln!(
+            f,
+            "; QUERY: {questions}, ANSWER: {answers}, AUTHORITY: {authorities}, ADDITIONAL: {additionals}"
+        )?;
 
-        for answer in value.answers.into_iter() {
-            buf.extend::<Vec<_>>(answer.into());
+        if let Some(edns) = &self.edns {
+            writeln!(f, "\n;; OPT PSEUDOSECTION:")?;
+            write!(f, "; EDNS: version: {}, flags:", edns.version)?;
+            if edns.dnssec_ok {
+                " do".fmt(f)?;
+            }
+            writeln!(f, "; udp: {}", edns.udp_payload_size)?;
+            for option in edns.options.iter() {
+                write!(f, "; OPT={}: ", option.code)?;
+                encoding::write_hex(f, &option.data)?;
+                writeln!(f)?;
+            }
         }
 
-        for authority in value.authorities.into_iter() {
-            buf.extend::<Vec<_>>(authority.into());
+        if !self.questions.is_empty() {
+            writeln!(f, "\n;; QUESTION SECTION:")?;
+            for question in self.questions.iter() {
+                writeln!(f, ";{question}")?;
+            }
         }
 
-        for additional in value.additionals.into_iter() {
-            buf.extend::<Vec<_>>(additional.into());
+        for (section, records) in [
+            ("ANSWER", &self.answers),
+            ("AUTHORITY", &self.authorities),
+            ("ADDITIONAL", &self.additionals),
+        ] {
+            if !records.is_empty() {
+                writeln!(f, "\n;; {section} SECTION:")?;
+                for record in records.iter() {
+                    writeln!(f, "{record}")?;
+                }
+            }
         }
 
-        buf
+        Ok(())
     }
 }
 
+/// The sections of a [`Message`] following its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Question,
+    Answer,
+    Authority,
+    Additional,
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::Question => "question".fmt(f),
+            Section::Answer => "answer".fmt(f),
+            Section::Authority => "authority".fmt(f),
+            Section::Additional => "additional".fmt(f),
+        }
+    }
+}
+
+/// The sections in the order they are written and counted by the header.
+const SECTIONS: [Section; 4] = [
+    Section::Question,
+    Section::Answer,
+    Section::Authority,
+    Section::Additional,
+];
+
+/// The header count of a section differs from its number of entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountMismatch {
+    pub section: Section,
+    /// The count of the header
+    pub count: u16,
+    /// The number of entries of the section
+    pub entries: usize,
+}
+
+impl Display for CountMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format!(
+            "the header counts '{}' {} entries, but the section holds '{}'",
+            self.count, self.section, self.entries
+        )
+        .fmt(f)
+    }
+}
+
+impl Error for CountMismatch {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MessageParseError {
     /// Messages are at least 12 bytes
@@ -157,6 +544,9 @@ pub enum MessageParseError {
     Header(HeaderParseError),
     Resource(ResourceRecordError),
     Question(QuestionParseError),
+    Edns(EdnsError),
+    /// Octets are left after the last record counted by the header
+    TrailingData(usize),
 }
 
 impl From<HeaderParseError> for MessageParseError {
@@ -177,6 +567,12 @@ impl From<QuestionParseError> for MessageParseError {
     }
 }
 
+impl From<EdnsError> for MessageParseError {
+    fn from(value: EdnsError) -> Self {
+        Self::Edns(value)
+    }
+}
+
 impl Display for MessageParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -184,6 +580,10 @@ impl Display for MessageParseError {
             MessageParseError::Header(err) => err.fmt(f),
             MessageParseError::Resource(err) => err.fmt(f),
             MessageParseError::Question(err) => err.fmt(f),
+            MessageParseError::Edns(err) => err.fmt(f),
+            MessageParseError::TrailingData(length) => {
+                format!("'{length}' bytes follow the last record of the message").fmt(f)
+            }
         }
     }
 }
@@ -194,86 +594,759 @@ impl TryFrom<&[u8]> for Message {
     type Error = MessageParseError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        if value.len() < 12 {
-            return Err(MessageParseError::ShortBuffer);
+        MessageRef::try_from(value)?.try_into()
+    }
+}
+
+#[cfg(test)]
+mod serializing {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    fn record(name: &str, data: ResourceData) -> ResourceRecord {
+        ResourceRecord {
+            name: Label::parse_str(name).unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data,
         }
-        let header: Header = value[..12].try_into()?;
+    }
 
-        let mut buf = &value[12..];
-        eprintln!("header: {header:?}");
-        eprintln!("buf: {buf:?}");
+    #[test]
+    fn compress_repeated_names() {
+        let mut message = Message::new(1234);
+        message
+            .ask("codecrafters.io", QuestionType::A, QuestionClass::IN)
+            .unwrap();
+        message.answer(record(
+            "codecrafters.io",
+            ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+        ));
+        message.answer(record(
+            "mail.codecrafters.io",
+            ResourceData::Address(Ipv4Addr::new(8, 8, 4, 4)),
+        ));
 
-        eprintln!("parsing questions");
-        let mut questions = vec![];
-        for _ in 0..header.question_count {
-            let (mut question, offset) = parse_question(buf)?;
+        let buf: Vec<u8> = message.try_into().unwrap();
 
-            expand_label(&mut question.name, value);
+        #[rustfmt::skip]
+        assert_eq!(
+            buf[12..],
+            [
+                // question
+                12, b'c', b'o', b'd', b'e', b'c', b'r', b'a', b'f', b't', b'e', b'r', b's',
+                2, b'i', b'o', 0,
+                0, 1, 0, 1,
+                // first answer, pointing to the question name
+                0xc0, 12,
+                0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 8, 8, 8, 8,
+                // second answer, pointing to the question name after its first label
+                4, b'm', b'a', b'i', b'l', 0xc0, 12,
+                0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 8, 8, 4, 4,
+            ]
+        );
 
-            eprintln!("question: {question:?}");
-            questions.push(question);
-            buf = &buf[offset..];
-            eprintln!("buf: {buf:?}");
-        }
+        let message = Message::try_from(&buf[..]).unwrap();
+        assert_eq!(
+            message.answers[1].name,
+            Label::parse_str("mail.codecrafters.io").unwrap()
+        );
+    }
 
-        eprintln!("parsing answers");
-        let mut answers = vec![];
-        for _ in 0..header.answer_count {
-            let (mut answer, offset) = parse_resource_record(buf)?;
+    #[test]
+    fn compress_names_inside_data() {
+        let mut message = Message::new(1234);
+        message.answer(record(
+            "codecrafters.io",
+            ResourceData::MailExchange {
+                preference: 10,
+                exchange: Label::parse_str("mail.codecrafters.io").unwrap(),
+            },
+        ));
+        message.answer(record(
+            "codecrafters.io",
+            ResourceData::Text(vec![CharacterString::String(b"codecrafters".to_vec())]),
+        ));
 
-            expand_label(&mut answer.name, value);
+        let buf: Vec<u8> = message.try_into().unwrap();
 
-            eprintln!("answer: {answer:?}");
-            answers.push(answer);
-            buf = &buf[offset..];
-            eprintln!("buf: {buf:?}");
-        }
+        #[rustfmt::skip]
+        assert_eq!(
+            buf[12..],
+            [
+                12, b'c', b'o', b'd', b'e', b'c', b'r', b'a', b'f', b't', b'e', b'r', b's',
+                2, b'i', b'o', 0,
+                0, 15, 0, 1, 0, 0, 0, 60, 0, 9,
+                0, 10, 4, b'm', b'a', b'i', b'l', 0xc0, 12,
+                0xc0, 12,
+                0, 16, 0, 1, 0, 0, 0, 60, 0, 13,
+                12, b'c', b'o', b'd', b'e', b'c', b'r', b'a', b'f', b't', b'e', b'r', b's',
+            ]
+        );
+    }
+
+    #[test]
+    fn point_into_record_data() {
+        let mut message = Message::new(1234);
+        message
+            .ask("codecrafters.io", QuestionType::MX, QuestionClass::IN)
+            .unwrap();
+        message.answer(record(
+            "codecrafters.io",
+            ResourceData::MailExchange {
+                preference: 10,
+                exchange: Label::parse_str("mail.codecrafters.io").unwrap(),
+            },
+        ));
+        message.add(record(
+            "mail.codecrafters.io",
+            ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+        ));
+        message.authorize(record(
+            "codecrafters.io",
+            ResourceData::SOA {
+                name: Label::parse_str("ns.codecrafters.io").unwrap(),
+                mail: Label::parse_str("admin.ns.codecrafters.io").unwrap(),
+                serial: 1,
+                refresh: 2,
+                retry: 3,
+                expire: 4,
+                minimum: 5,
+            },
+        ));
+        message.sync_counts();
 
-        eprintln!("parsing authorities");
-        let mut authorities = vec![];
-        for _ in 0..header.authority_count {
-            let (mut authority, offset) = parse_resource_record(buf)?;
+        let buf: Vec<u8> = message.clone().try_into().unwrap();
 
-            expand_label(&mut authority.name, value);
+        // the exchange starts after the question, the owner, type, class, TTL, length and
+        // preference of the answer
+        let exchange = 33 + 2 + 10 + 2;
+        assert_eq!(
+            buf[exchange..exchange + 7],
+            [4, b'm', b'a', b'i', b'l', 0xc0, 12]
+        );
 
-            eprintln!("authority: {authority:?}");
-            authorities.push(authority);
-            buf = &buf[offset..];
-            eprintln!("buf: {buf:?}");
+        // the mailbox of the SOA points to its name server, within the same data
+        let soa = exchange + 7 + 12;
+        #[rustfmt::skip]
+        assert_eq!(
+            buf[soa..soa + 13],
+            [2, b'n', b's', 0xc0, 12, 5, b'a', b'd', b'm', b'i', b'n', 0xc0, soa as u8]
+        );
+
+        // the owner of the additional record points to the exchange
+        let additional = soa + 13 + 20;
+        assert_eq!(buf[additional..additional + 2], [0xc0, exchange as u8]);
+        assert_eq!(buf.len(), additional + 2 + 14);
+
+        assert_eq!(Message::try_from(&buf[..]), Ok(message));
+    }
+
+    #[test]
+    fn pointers_start_at_the_message() {
+        let mut message = Message::new(1234);
+        message
+            .ask("codecrafters.io", QuestionType::A, QuestionClass::IN)
+            .unwrap();
+        message.answer(record(
+            "codecrafters.io",
+            ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+        ));
+        let expected = Vec::try_from(message.clone()).unwrap();
+
+        // e.g. the length prefix of a TCP message
+        let mut buf = vec![0, expected.len() as u8];
+        message.write(&mut buf).unwrap();
+        assert_eq!(buf[2..], expected[..]);
+    }
+
+    #[test]
+    fn compress_beyond_known_suffixes() {
+        let mut message = Message::new(1234);
+        for index in 0..200 {
+            message.answer(record(
+                &format!("host{index}.codecrafters.io"),
+                ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+            ));
         }
 
-        eprintln!("parsing additionals");
-        let mut additionals = vec![];
-        for _ in 0..header.addtional_count {
-            let (mut additional, offset) = parse_resource_record(buf)?;
+        message.sync_counts();
+
+        // the suffixes of the last names aren't remembered, but the names are still written
+        let buf = Vec::try_from(message.clone()).unwrap();
+        assert_eq!(Message::try_from(&buf[..]), Ok(message));
+    }
+
+    #[test]
+    fn counts_follow_sections() {
+        let mut message = Message::new(1234);
+        message.answers.push(record(
+            "codecrafters.io",
+            ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+        ));
+        message.header.authority_count = 3;
+        message.set_edns(Some(Edns::new(1232)));
+        assert_eq!(
+            message.validate_counts(),
+            Err(CountMismatch {
+                section: Section::Answer,
+                count: 0,
+                entries: 1,
+            })
+        );
+
+        let buf: Vec<u8> = message.try_into().unwrap();
+        assert_eq!(buf[4..12], [0, 0, 0, 1, 0, 0, 0, 1]);
 
-            expand_label(&mut additional.name, value);
+        let message = Message::try_from(&buf[..]).unwrap();
+        assert_eq!(message.validate_counts(), Ok(()));
+
+        // octets left after the counted records are reported rather than ignored
+        let mut buf = buf;
+        buf.extend_from_slice(&[0, 0]);
+        assert_eq!(
+            Message::try_from(&buf[..]),
+            Err(MessageParseError::TrailingData(2))
+        );
+        buf[7] = 2;
+        assert_eq!(
+            Message::try_from(&buf[..]),
+            Err(MessageParseError::Resource(ResourceRecordError::Truncated))
+        );
+    }
+
+    #[test]
+    fn counts_are_not_kept_by_hand() {
+        let mut message = Message::new(1234);
+        message.set_edns(Some(Edns::new(1232)));
+        message.header.addtional_count = 0;
+        message.set_edns(None);
+        assert_eq!(message.header.addtional_count, 0);
+
+        let buf: Vec<u8> = message.try_into().unwrap();
+        assert_eq!(buf[4..12], [0; 8]);
+    }
 
-            eprintln!("additional: {additional:?}");
-            additionals.push(additional);
-            buf = &buf[offset..];
-            eprintln!("buf: {buf:?}");
+    #[test]
+    fn too_many_entries() {
+        let mut message = Message::new(1234);
+        let question = Question {
+            name: Label::default(),
+            typ: QuestionType::A,
+            class: QuestionClass::IN,
+        };
+        message.questions = vec![question; u16::MAX as usize + 1];
+        message.sync_counts();
+        assert_eq!(message.header.question_count, u16::MAX);
+        assert_eq!(
+            message.validate_counts(),
+            Err(CountMismatch {
+                section: Section::Question,
+                count: u16::MAX,
+                entries: u16::MAX as usize + 1,
+            })
+        );
+
+        // the header couldn't tell where the questions end
+        assert_eq!(
+            Vec::try_from(message.clone()),
+            Err(EncodeError::TooManyEntries {
+                section: Section::Question,
+                entries: u16::MAX as usize + 1,
+            })
+        );
+
+        message.questions.pop();
+        let buf: Vec<u8> = message.try_into().unwrap();
+        assert_eq!(buf[4..6], [0xff, 0xff]);
+    }
+
+    #[test]
+    fn truncate_whole_rrsets() {
+        let mut message = Message::new(1234);
+        message
+            .ask("codecrafters.io", QuestionType::A, QuestionClass::IN)
+            .unwrap();
+        for address in [1, 2] {
+            message.answer(record(
+                "codecrafters.io",
+                ResourceData::Address(Ipv4Addr::new(8, 8, 8, address)),
+            ));
+        }
+        message.authorize(record(
+            "codecrafters.io",
+            ResourceData::NameServer(Label::parse_str("ns.codecrafters.io").unwrap()),
+        ));
+        for address in [1, 2] {
+            message.add(record(
+                "ns.codecrafters.io",
+                ResourceData::Address(Ipv4Addr::new(8, 8, 4, address)),
+            ));
         }
+        message.set_edns(Some(Edns::new(1232)));
+
+        let full = message.to_wire_with_limit(512).unwrap();
+        assert_eq!(full, Vec::try_from(message.clone()).unwrap());
+
+        // dropping the additional records doesn't truncate the message
+        let limited =
+            Message::try_from(&message.to_wire_with_limit(full.len() - 1).unwrap()[..]).unwrap();
+        assert!(!limited.header.truncated_message);
+        assert_eq!(limited.answers, message.answers);
+        assert_eq!(limited.authorities, message.authorities);
+        assert!(limited.additionals.is_empty());
+        assert_eq!(limited.edns, message.edns);
+
+        // the header and question take 33 octets, the answers 16 each, the authority 17 and the
+        // pseudo record 11
+        let limited = message.to_wire_with_limit(33 + 32 + 17 + 11).unwrap();
+        assert_eq!(limited.len(), 93);
+        let limited = Message::try_from(&limited[..]).unwrap();
+        assert!(!limited.header.truncated_message);
+        assert_eq!(limited.authorities, message.authorities);
+
+        let limited = message.to_wire_with_limit(92).unwrap();
+        assert_eq!(limited.len(), 76);
+        let limited = Message::try_from(&limited[..]).unwrap();
+        assert!(limited.header.truncated_message);
+        assert_eq!(limited.answers, message.answers);
+        assert!(limited.authorities.is_empty());
+        assert!(limited.additionals.is_empty());
+
+        // the first answer would fit, but not without the other one of its RRset
+        let limited = Message::try_from(&message.to_wire_with_limit(70).unwrap()[..]).unwrap();
+        assert!(limited.header.truncated_message);
+        assert!(limited.answers.is_empty());
+        assert!(limited.authorities.is_empty());
+        assert_eq!(limited.questions, message.questions);
+        assert_eq!(limited.edns, message.edns);
+
+        let limited = Message::try_from(&message.to_wire_with_limit(0).unwrap()[..]).unwrap();
+        assert!(limited.header.truncated_message);
+        assert_eq!(limited.questions, message.questions);
+    }
+
+    #[test]
+    fn truncate_scattered_rrsets() {
+        let mut message = Message::new(1234);
+        message
+            .ask("codecrafters.io", QuestionType::ALL, QuestionClass::IN)
+            .unwrap();
+        let address = |address| {
+            record(
+                "codecrafters.io",
+                ResourceData::Address(Ipv4Addr::new(8, 8, 8, address)),
+            )
+        };
+        let text = record(
+            "codecrafters.io",
+            ResourceData::Text(vec![CharacterString::String(b"codecrafters".to_vec())]),
+        );
+        message.answer(address(1));
+        message.answer(text.clone());
+        message.answer(address(2));
+
+        // the last address is dropped along with the first one, which comes before the text
+        let full = Vec::try_from(message.clone()).unwrap();
+        let limited =
+            Message::try_from(&message.to_wire_with_limit(full.len() - 1).unwrap()[..]).unwrap();
+        assert!(limited.header.truncated_message);
+        assert_eq!(limited.answers, [text]);
+    }
+
+    #[test]
+    fn standalone_records_are_not_compressed() {
+        let soa = record(
+            "codecrafters.io",
+            ResourceData::SOA {
+                name: Label::parse_str("ns.codecrafters.io").unwrap(),
+                mail: Label::parse_str("admin.codecrafters.io").unwrap(),
+                serial: 1,
+                refresh: 2,
+                retry: 3,
+                expire: 4,
+                minimum: 5,
+            },
+        );
+
+        let buf: Vec<u8> = soa.try_into().unwrap();
+        assert!(!buf.iter().any(|&byte| byte & 0b1100_0000 == 0b1100_0000));
+    }
+}
+
+#[cfg(test)]
+mod displaying {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    #[test]
+    fn dig_output() {
+        let mut message = Message::new(1234);
+        message.header.recursion_desired = true;
+        message
+            .ask("codecrafters.io", QuestionType::A, QuestionClass::IN)
+            .unwrap();
+        message.respond();
+        message.answer(ResourceRecord {
+            name: Label::parse_str("codecrafters.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+        });
+        message.set_edns(Some(Edns {
+            dnssec_ok: true,
+            options: vec![EdnsOption {
+                code: 10,
+                data: vec![0xde, 0xad],
+            }],
+            ..Edns::new(1232)
+        }));
+        message.set_response_code(ResponseCode::BadVersion);
+
+        assert_eq!(
+            message.to_string(),
+            ";; ->>HEADER<<- opcode: QUERY, status: BADVERS, id: 1234
+;; flags: qr rd; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 1
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags: do; udp: 1232
+; OPT=10: dead
+
+;; QUESTION SECTION:
+;codecrafters.io. IN A
+
+;; ANSWER SECTION:
+codecrafters.io. 60 IN A 8.8.8.8
+"
+        );
+    }
+
+    #[test]
+    fn empty_sections_are_left_out() {
+        let mut message = Message::new(7);
+        message.query();
+        message.header.operation_code = OperationCode::Reserved(3);
+        message.set_response_code(ResponseCode::Refused);
+
+        assert_eq!(
+            message.to_string(),
+            ";; ->>HEADER<<- opcode: RESERVED3, status: REFUSED, id: 7
+;; flags:; QUERY: 0, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 0
+"
+        );
+    }
+
+    #[test]
+    fn counts_of_the_sections() {
+        let mut message = Message::new(7);
+        message.header.answer_count = 3;
+        message.additionals.push(ResourceRecord {
+            name: Label::parse_str("codecrafters.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+        });
 
-        Ok(Self {
-            header,
-            questions,
-            answers,
-            authorities,
-            additionals,
-        })
+        assert!(message
+            .to_string()
+            .contains("; QUERY: 0, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 1\n"));
     }
 }
 
-fn expand_label(label: &mut Label, buf: &[u8]) {
-    let last = label.0.pop();
-    if let Some(CharacterString::Compressed(offset)) = last {
-        eprintln!("decompressing label at index {offset}");
-        let (expanded_label, _) =
-            parse_label(&buf[offset as usize..]).expect("false compressed offset");
-        label.0.extend(expanded_label.0);
-        expand_label(label, buf) // in-case that the expanded label is also compressed
-    } else if let Some(last) = last {
-        label.0.push(last);
+#[cfg(test)]
+mod parsing {
+    use std::net::Ipv4Addr;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        let mut message = Message::new(1234);
+        message
+            .ask("codecrafters.io", QuestionType::MX, QuestionClass::IN)
+            .unwrap();
+        message.answer(ResourceRecord {
+            name: Label::parse_str("codecrafters.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::MailExchange {
+                preference: 10,
+                exchange: Label::parse_str("mail.codecrafters.io").unwrap(),
+            },
+        });
+        message.authorize(ResourceRecord {
+            name: Label::parse_str("codecrafters.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::SOA {
+                name: Label::parse_str("ns.codecrafters.io").unwrap(),
+                mail: Label::parse_str("admin.codecrafters.io").unwrap(),
+                serial: 1,
+                refresh: 2,
+                retry: 3,
+                expire: 4,
+                minimum: 5,
+            },
+        });
+        message.add(ResourceRecord {
+            name: Label::parse_str("mail.codecrafters.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+        });
+        message.add(ResourceRecord {
+            name: Label::parse_str("mail.codecrafters.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::Text(vec![CharacterString::String(b"v=spf1 -all".to_vec())]),
+        });
+        message.try_into().unwrap()
+    }
+
+    #[test]
+    fn decompress_names_inside_data() {
+        let buf = sample();
+        // the exchange and the names of the SOA record point to the question
+        assert!(buf.windows(2).filter(|pair| pair == &[0xc0, 12]).count() > 3);
+
+        let message = Message::try_from(&buf[..]).unwrap();
+        assert_eq!(
+            message.answers[0].data,
+            ResourceData::MailExchange {
+                preference: 10,
+                exchange: Label::parse_str("mail.codecrafters.io").unwrap(),
+            }
+        );
+        let ResourceData::SOA { name, mail, .. } = &message.authorities[0].data else {
+            panic!("expected a SOA record");
+        };
+        assert_eq!(name, &Label::parse_str("ns.codecrafters.io").unwrap());
+        assert_eq!(mail, &Label::parse_str("admin.codecrafters.io").unwrap());
+        assert_eq!(Vec::try_from(message).unwrap(), buf);
+    }
+
+    /// A response of id 7 to the question `codecrafters.io. A IN`, followed by `records`
+    fn response(counts: [u8; 3], records: &[u8]) -> Vec<u8> {
+        let [answers, authorities, additionals] = counts;
+        #[rustfmt::skip]
+        let mut buf = vec![
+            0, 7, 0x84, 0, 0, 1, 0, answers, 0, authorities, 0, additionals,
+            12, b'c', b'o', b'd', b'e', b'c', b'r', b'a', b'f', b't', b'e', b'r', b's',
+            2, b'i', b'o', 0,
+            0, 1, 0, 1,
+        ];
+        buf.extend_from_slice(records);
+        buf
+    }
+
+    #[test]
+    fn decompress_both_names_of_soa() {
+        // the name server points to the question, and the mailbox to the name server
+        #[rustfmt::skip]
+        let buf = response([0, 1, 0], &[
+            0xc0, 12, 0, 6, 0, 1, 0, 0, 0, 60, 0, 33,
+            2, b'n', b's', 0xc0, 12,
+            5, b'a', b'd', b'm', b'i', b'n', 0xc0, 45,
+            0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5,
+        ]);
+
+        let message = Message::try_from(&buf[..]).unwrap();
+        let ResourceData::SOA { name, mail, .. } = &message.authorities[0].data else {
+            panic!("expected a SOA record");
+        };
+        assert_eq!(name, &Label::parse_str("ns.codecrafters.io").unwrap());
+        assert_eq!(mail, &Label::parse_str("admin.ns.codecrafters.io").unwrap());
+        assert_eq!(Vec::try_from(message).unwrap(), buf);
+    }
+
+    #[test]
+    fn follow_pointer_chains_through_data() {
+        // the owner of the additional record points to the exchange, which points to the question
+        #[rustfmt::skip]
+        let buf = response([1, 0, 1], &[
+            0xc0, 12, 0, 15, 0, 1, 0, 0, 0, 60, 0, 9,
+            0, 10, 4, b'm', b'a', b'i', b'l', 0xc0, 12,
+            3, b'w', b'w', b'w', 0xc0, 47, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4,
+            8, 8, 8, 8,
+        ]);
+
+        let message = Message::try_from(&buf[..]).unwrap();
+        assert_eq!(
+            message.additionals[0].name,
+            Label::parse_str("www.mail.codecrafters.io").unwrap()
+        );
+        assert_eq!(Vec::try_from(message).unwrap(), buf);
+    }
+
+    #[test]
+    fn reject_pointers_past_the_data() {
+        // the exchange points to the owner of the next record, past the end of its data
+        #[rustfmt::skip]
+        let buf = response([1, 0, 1], &[
+            0xc0, 12, 0, 15, 0, 1, 0, 0, 0, 60, 0, 4,
+            0, 10, 0xc0, 49,
+            4, b'm', b'a', b'i', b'l', 0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4,
+            8, 8, 8, 8,
+        ]);
+
+        let message = MessageRef::try_from(&buf[..]).unwrap();
+        let answer = message.answers().next().unwrap().unwrap();
+        assert_eq!(
+            answer.data(),
+            Err(ResourceDataError::Label(LabelError::InvalidPointer(49)))
+        );
+        assert_eq!(
+            Message::try_from(&buf[..]),
+            Err(MessageParseError::Resource(ResourceRecordError::Data(
+                ResourceDataError::Label(LabelError::InvalidPointer(49))
+            )))
+        );
+    }
+
+    #[test]
+    fn responses_clear_query_bits() {
+        let mut buf = sample();
+        // a query with the Z, AD and CD bits set
+        buf[2] = 0b0000_0001;
+        buf[3] = 0b0111_0000;
+
+        let mut message = Message::try_from(&buf[..]).unwrap();
+        assert!(message.header.reserved && message.header.authentic_data);
+
+        message.respond();
+        assert_eq!(message.header.typ, PacketType::Response);
+        assert!(!message.header.reserved);
+        assert!(!message.header.authentic_data);
+        assert!(message.header.checking_disabled);
+        assert!(message.header.recursion_desired);
+    }
+
+    #[test]
+    fn truncated_messages() {
+        let buf = sample();
+        assert!(Message::try_from(&buf[..]).is_ok());
+
+        for length in 0..buf.len() {
+            assert!(Message::try_from(&buf[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn overrunning_data_length() {
+        let mut buf = sample();
+        // the data length of the last record
+        let length = buf.len() - 14;
+        buf[length] = 0xff;
+
+        assert_eq!(
+            Message::try_from(&buf[..]),
+            Err(MessageParseError::Resource(
+                ResourceRecordError::DataOverrun {
+                    length: 0xff0c,
+                    remaining: 12
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn pointer_outside_of_message() {
+        let mut buf = sample();
+        // the name of the first answer points to the question
+        buf[12 + 21 + 1] = 0xff;
+
+        assert_eq!(
+            Message::try_from(&buf[..]),
+            Err(MessageParseError::Resource(ResourceRecordError::Label(
+                LabelError::InvalidPointer(0xff)
+            )))
+        );
+    }
+
+    #[test]
+    fn lift_opt_record_into_edns() {
+        #[rustfmt::skip]
+        let buf = [
+            0x04, 0xd2, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 1,
+            7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0, 0, 28, 0, 1,
+            0, 0, 41, 0x04, 0xd0, 0, 0, 0, 0, 0, 0,
+        ];
+
+        let message = Message::try_from(&buf[..]).unwrap();
+        assert!(message.additionals.is_empty());
+        assert_eq!(message.edns, Some(Edns::new(1232)));
+        assert_eq!(message.max_payload_size(), 1232);
+        assert_eq!(Vec::try_from(message).unwrap(), buf);
+
+        let mut duplicated = buf.to_vec();
+        duplicated[11] = 2;
+        duplicated.extend_from_slice(&buf[25..]);
+        assert_eq!(
+            Message::try_from(&duplicated[..]),
+            Err(MessageParseError::Edns(EdnsError::Duplicate))
+        );
+    }
+
+    #[test]
+    fn extended_response_code() {
+        let mut message = Message::new(7);
+        message.set_response_code(ResponseCode::BadVersion);
+        assert_eq!(message.header.response, ResponseCode::NoError);
+        assert!(message.edns.is_some());
+
+        let message = Message::try_from(&Vec::try_from(message).unwrap()[..]).unwrap();
+        assert_eq!(message.edns.as_ref().unwrap().extended_rcode, 1);
+        assert_eq!(message.response_code(), ResponseCode::BadVersion);
+
+        let mut message = Message::new(7);
+        message.set_response_code(ResponseCode::NxRrSet);
+        assert_eq!(message.edns, None);
+        assert_eq!(message.response_code(), ResponseCode::NxRrSet);
+
+        // an extended code set on the header directly moves into an OPT record when written
+        let mut message = Message::new(7);
+        message.header.response = ResponseCode::BadCookie;
+        assert_eq!(message.response_code(), ResponseCode::BadCookie);
+
+        let buf = Vec::try_from(message).unwrap();
+        assert_eq!(buf[3] & 0b1111, 7);
+        assert_eq!(buf[10..12], [0, 1]);
+        let message = Message::try_from(&buf[..]).unwrap();
+        assert_eq!(message.response_code(), ResponseCode::BadCookie);
+
+        let mut message = Message::new(7);
+        message.set_edns(Some(Edns::new(1232)));
+        message.header.response = ResponseCode::BadVersion;
+        let message = Message::try_from(&Vec::try_from(message).unwrap()[..]).unwrap();
+        assert_eq!(message.header.response, ResponseCode::NoError);
+        assert_eq!(message.edns.as_ref().unwrap().udp_payload_size, 1232);
+        assert_eq!(message.response_code(), ResponseCode::BadVersion);
+    }
+
+    #[test]
+    fn corrupted_messages_never_panic() {
+        let sample = sample();
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+
+        for _ in 0..10_000 {
+            let mut buf = sample.clone();
+            for _ in 0..rng.gen_range(1..8) {
+                let index = rng.gen_range(0..buf.len());
+                buf[index] = rng.gen();
+            }
+            buf.truncate(rng.gen_range(0..=buf.len()));
+
+            let _ = Message::try_from(&buf[..]);
+        }
+
+        for _ in 0..10_000 {
+            let length = rng.gen_range(0..512);
+            let buf = (0..length).map(|_| rng.gen()).collect::<Vec<u8>>();
+
+            let _ = Message::try_from(&buf[..]);
+        }
     }
 }
@@ -2,7 +2,7 @@
 //! parameters that define what is being asked.  The section contains [`QDCOUNT`] (usually 1)
 //! entries, each of the following format:
 //!
-//! ```txt`
+//! ```txt
 //!                                     1  1  1  1  1  1
 //!       0  1  2  3  4  5  6  7  8  9  0  1  2  3  4  5
 //!     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
@@ -22,15 +22,18 @@
 use std::{
     error::Error,
     fmt::{self, Display},
+    str::FromStr,
 };
 
-use bytes::{Buf, BufMut};
+use bytes::Buf;
 
 use crate::message::parse_label;
 
 use super::{
-    label::{Label, LabelError},
-    type_class::{QuestionClass, QuestionType, UnregisteredClass, UnregisteredType},
+    label::{Label, LabelCompressor, LabelError},
+    resource::split_tokens,
+    type_class::{QuestionClass, QuestionType},
+    wire::Sink,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -52,22 +55,10 @@ pub struct Question {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum QuestionParseError {
     Label(LabelError),
-    InvalidType(UnregisteredType),
-    InvalidClass(UnregisteredClass),
     MissingTypeAndClass,
     MissingClass,
-}
-
-impl From<UnregisteredType> for QuestionParseError {
-    fn from(value: UnregisteredType) -> Self {
-        Self::InvalidType(value)
-    }
-}
-
-impl From<UnregisteredClass> for QuestionParseError {
-    fn from(value: UnregisteredClass) -> Self {
-        Self::InvalidClass(value)
-    }
+    /// An unexpected token was found in the presentation format of the question
+    Syntax(String),
 }
 
 impl From<LabelError> for QuestionParseError {
@@ -81,10 +72,9 @@ impl Display for QuestionParseError {
         use QuestionParseError::*;
         match self {
             Label(err) => err.fmt(f),
-            InvalidType(err) => err.fmt(f),
-            InvalidClass(err) => err.fmt(f),
             MissingTypeAndClass => "buffer doesn't contain a type and a class".fmt(f),
             MissingClass => "buffer doesn't contain a class".fmt(f),
+            Syntax(token) => format!("unexpected '{token}' in question").fmt(f),
         }
     }
 }
@@ -109,8 +99,8 @@ pub fn parse_question(value: &[u8]) -> Result<(Question, usize), QuestionParseEr
         0 | 1 => return Err(MissingTypeAndClass),
         2 | 3 => return Err(MissingClass),
         _ => {
-            typ = buf.get_u16().try_into()?;
-            class = buf.get_u16().try_into()?;
+            typ = buf.get_u16().into();
+            class = buf.get_u16().into();
         }
     }
     question_offset += 4;
@@ -126,17 +116,95 @@ impl TryFrom<&[u8]> for Question {
     }
 }
 
+impl Question {
+    /// Appends the wire format of the question to `buf`, see [`Label::write`].
+    pub fn write<'a>(&'a self, buf: &mut impl Sink, names: Option<&mut LabelCompressor<'a>>) {
+        // writing labels
+        self.name.write(buf, names);
+
+        // writing type and class
+        buf.put_u16(self.typ.into());
+        buf.put_u16(self.class.into());
+    }
+}
+
 impl From<Question> for Vec<u8> {
     fn from(value: Question) -> Self {
         let mut buf = vec![];
+        value.write(&mut buf, None);
+        buf
+    }
+}
 
-        // writing labels
-        buf.extend::<Vec<_>>(value.name.into());
+impl Display for Question {
+    /// Presents the question as its name, class and type, e.g. `example.com. IN A`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.name, self.class, self.typ)
+    }
+}
 
-        // writing type and class
-        buf.put_u16(value.typ as u16);
-        buf.put_u16(value.class as u16);
+impl FromStr for Question {
+    type Err = QuestionParseError;
+
+    /// Parses `<name> [<class>] <type>`, where the class defaults to IN.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use QuestionParseError::{MissingTypeAndClass, Syntax};
+
+        let tokens = split_tokens(s).ok_or_else(|| Syntax(s.to_owned()))?;
+        let (name, class, typ) = match tokens.as_slice() {
+            [name, typ] => (name, None, typ),
+            [name, class, typ] => (name, Some(class), typ),
+            [_] => return Err(MissingTypeAndClass),
+            _ => return Err(Syntax(s.to_owned())),
+        };
+
+        Ok(Question {
+            name: name.parse()?,
+            typ: typ.parse().map_err(|_| Syntax(typ.to_string()))?,
+            class: match class {
+                Some(class) => class.parse().map_err(|_| Syntax(class.to_string()))?,
+                None => QuestionClass::IN,
+            },
+        })
+    }
+}
 
-        buf
+#[cfg(test)]
+mod presentation {
+    use super::*;
+
+    #[test]
+    fn round_trip_question() {
+        let question = "example.com. CH TXT".parse::<Question>().unwrap();
+        assert_eq!(question.class, QuestionClass::CH);
+        assert_eq!(question.typ, QuestionType::TXT);
+        assert_eq!(question.to_string(), "example.com. CH TXT");
+
+        assert_eq!(
+            "Example.com any".parse(),
+            Ok(Question {
+                name: Label::parse_str("example.com").unwrap(),
+                typ: QuestionType::ALL,
+                class: QuestionClass::IN,
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_question() {
+        use QuestionParseError::*;
+        assert_eq!("example.com.".parse::<Question>(), Err(MissingTypeAndClass));
+        assert_eq!(
+            "example.com. IN".parse::<Question>(),
+            Err(Syntax("IN".to_owned()))
+        );
+        assert_eq!(
+            "example.com. A IN".parse::<Question>(),
+            Err(Syntax("IN".to_owned()))
+        );
+        assert_eq!(
+            "a..b A".parse::<Question>(),
+            Err(Label(LabelError::EmptyLabel))
+        );
     }
 }
@@ -0,0 +1,529 @@
+//! The resource records of the DNS security extensions ([RFC4034] and [RFC5155]).
+//!
+//! None of the domain names held by these records are compressed, and the presentation format
+//! of their binary fields is either base64 (keys and signatures), hexadecimal (digests and salts)
+//! or base32hex (hashed owner names).
+//!
+//! [RFC4034]: <https://datatracker.ietf.org/doc/html/rfc4034>
+//! [RFC5155]: <https://datatracker.ietf.org/doc/html/rfc5155>
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use super::{take, ResourceData, ResourceDataError, ResourceType};
+use crate::message::{parse_label, Sink};
+
+/// The types existing at the owner name of an NSEC or NSEC3 record.
+///
+/// The type space is split into 256 window blocks, each of which is encoded as its number, the
+/// length of its bit map and up to 32 octets where every bit represents a type:
+///
+/// ```txt
+///     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+///     |  Window Block #       |  Bitmap Length        |
+///     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+///     /                    Bitmap                     /
+///     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// Blocks without any types and trailing zero octets are never encoded, so a valid bit map has a
+/// single wire format.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TypeBitmap(BTreeSet<u16>);
+
+impl TypeBitmap {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Whether the bit of `typ` is set
+    pub fn contains(&self, typ: ResourceType) -> bool {
+        self.0.contains(&typ.into())
+    }
+
+    /// Set the bit of `typ`
+    pub fn insert(&mut self, typ: ResourceType) {
+        self.0.insert(typ.into());
+    }
+
+    /// Clear the bit of `typ`
+    pub fn remove(&mut self, typ: ResourceType) {
+        self.0.remove(&typ.into());
+    }
+
+    /// The types whose bit is set, in ascending order
+    pub fn types(&self) -> impl Iterator<Item = ResourceType> + '_ {
+        self.0.iter().map(|&typ| typ.into())
+    }
+
+    /// Parses the window blocks filling `value`.
+    pub fn parse(value: &[u8]) -> Result<Self, ResourceDataError> {
+        use ResourceDataError::{InvalidTypeBitmap, Truncated};
+
+        let mut buf = value;
+        let mut types = BTreeSet::new();
+        let mut last_window = None;
+
+        while !buf.is_empty() {
+            let [window, length] = take(&mut buf).ok_or(Truncated)?;
+            let length = length as usize;
+
+            if last_window.is_some_and(|last| last >= window) || !(1..=32).contains(&length) {
+                return Err(InvalidTypeBitmap);
+            }
+            last_window = Some(window);
+
+            let bit_map = buf.get(..length).ok_or(Truncated)?;
+            buf = &buf[length..];
+
+            if bit_map[length - 1] == 0 {
+                return Err(InvalidTypeBitmap);
+            }
+
+            for (index, octet) in bit_map.iter().enumerate() {
+                for bit in (0..8).filter(|bit| octet & (0b1000_0000 >> bit) != 0) {
+                    types.insert(u16::from_be_bytes([window, (index * 8 + bit) as u8]));
+                }
+            }
+        }
+
+        Ok(Self(types))
+    }
+
+    /// Appends the wire format of the bit map to `buf`.
+    pub fn write(&self, buf: &mut impl Sink) {
+        let mut types = self.0.iter().peekable();
+
+        while let Some(&first) = types.peek() {
+            let [window, _] = first.to_be_bytes();
+            let mut bit_map = [0u8; 32];
+            let mut length = 0;
+
+            while let Some(&typ) = types.next_if(|typ| typ.to_be_bytes()[0] == window) {
+                let [_, bit] = typ.to_be_bytes();
+                bit_map[bit as usize / 8] |= 0b1000_0000 >> (bit % 8);
+                length = bit as usize / 8 + 1;
+            }
+
+            buf.put_u8(window);
+            buf.put_u8(length as u8);
+            buf.put_slice(&bit_map[..length]);
+        }
+    }
+}
+
+impl FromIterator<ResourceType> for TypeBitmap {
+    fn from_iter<T: IntoIterator<Item = ResourceType>>(iter: T) -> Self {
+        Self(iter.into_iter().map(u16::from).collect())
+    }
+}
+
+impl Display for TypeBitmap {
+    /// Lists the type mnemonics separated by spaces.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, typ) in self.types().enumerate() {
+            if index > 0 {
+                " ".fmt(f)?;
+            }
+            typ.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a signature expiration or inception time as `YYYYMMDDHHmmSS` in UTC.
+pub fn write_timestamp(f: &mut impl fmt::Write, timestamp: u32) -> fmt::Result {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    write!(
+        f,
+        "{year:04}{month:02}{day:02}{:02}{:02}{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Reads a signature expiration or inception time, either as `YYYYMMDDHHmmSS` in UTC or as the
+/// number of seconds since the epoch.
+pub fn parse_timestamp(value: &str) -> Option<u32> {
+    if !value.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    if value.len() != 14 {
+        return value.parse().ok();
+    }
+
+    let field = |range: std::ops::Range<usize>| value[range].parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+    let (hour, minute, second) = (field(8..10)?, field(10..12)?, field(12..14)?);
+
+    let leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if leap_year => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    // <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>
+    let year = year - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    (days * 86400 + hour * 3600 + minute * 60 + second)
+        .try_into()
+        .ok()
+}
+
+impl ResourceData {
+    pub(super) fn parse_dns_key(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let [f0, f1, protocol, algorithm] = take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+        Ok(Self::DnsKey {
+            flags: u16::from_be_bytes([f0, f1]),
+            protocol,
+            algorithm,
+            public_key: buf.to_vec(),
+        })
+    }
+
+    pub(super) fn parse_signature(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let [t0, t1, algorithm, labels, l0, l1, l2, l3, e0, e1, e2, e3, i0, i1, i2, i3, k0, k1] =
+            take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+        let (signer, offset) = parse_label(buf)?;
+
+        Ok(Self::Signature {
+            type_covered: u16::from_be_bytes([t0, t1]).into(),
+            algorithm,
+            labels,
+            original_time_to_live: u32::from_be_bytes([l0, l1, l2, l3]),
+            expiration: u32::from_be_bytes([e0, e1, e2, e3]),
+            inception: u32::from_be_bytes([i0, i1, i2, i3]),
+            key_tag: u16::from_be_bytes([k0, k1]),
+            signer,
+            signature: buf[offset..].to_vec(),
+        })
+    }
+
+    pub(super) fn parse_delegation_signer(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let [k0, k1, algorithm, digest_type] =
+            take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+        Ok(Self::DelegationSigner {
+            key_tag: u16::from_be_bytes([k0, k1]),
+            algorithm,
+            digest_type,
+            digest: buf.to_vec(),
+        })
+    }
+
+    pub(super) fn parse_next_secure(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let (next, offset) = parse_label(value)?;
+        Ok(Self::NextSecure {
+            next,
+            types: TypeBitmap::parse(&value[offset..])?,
+        })
+    }
+
+    pub(super) fn parse_hashed_next_secure(
+        value: &[u8],
+    ) -> Result<ResourceData, ResourceDataError> {
+        use ResourceDataError::Truncated;
+
+        let mut buf = value;
+        let (hash_algorithm, flags, iterations, salt) = parse_hash_parameters(&mut buf)?;
+
+        let [length] = take(&mut buf).ok_or(Truncated)?;
+        let next_hashed = buf.get(..length as usize).ok_or(Truncated)?.to_vec();
+        buf = &buf[length as usize..];
+
+        Ok(Self::HashedNextSecure {
+            hash_algorithm,
+            flags,
+            iterations,
+            salt,
+            next_hashed,
+            types: TypeBitmap::parse(buf)?,
+        })
+    }
+
+    pub(super) fn parse_hashed_next_secure_parameters(
+        value: &[u8],
+    ) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let (hash_algorithm, flags, iterations, salt) = parse_hash_parameters(&mut buf)?;
+
+        match buf.is_empty() {
+            true => Ok(Self::HashedNextSecureParameters {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+            }),
+            false => Err(ResourceDataError::InvalidLength {
+                expected: value.len() - buf.len(),
+                found: value.len(),
+            }),
+        }
+    }
+}
+
+/// Reads the hash algorithm, flags, iterations and salt shared by NSEC3 and NSEC3PARAM.
+fn parse_hash_parameters(buf: &mut &[u8]) -> Result<(u8, u8, u16, Vec<u8>), ResourceDataError> {
+    use ResourceDataError::Truncated;
+
+    let [hash_algorithm, flags, i0, i1, length] = take(buf).ok_or(Truncated)?;
+    let salt = buf.get(..length as usize).ok_or(Truncated)?.to_vec();
+    *buf = &buf[length as usize..];
+
+    Ok((hash_algorithm, flags, u16::from_be_bytes([i0, i1]), salt))
+}
+
+#[cfg(test)]
+mod type_bit_map {
+    use super::*;
+
+    #[test]
+    fn rfc4034_example() {
+        // A MX RRSIG NSEC TYPE1234
+        #[rustfmt::skip]
+        let buf = [
+            0x00, 0x06, 0x40, 0x01, 0x00, 0x00, 0x00, 0x03,
+            0x04, 0x1b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x20,
+        ];
+
+        let types = TypeBitmap::parse(&buf).unwrap();
+        assert_eq!(
+            types.types().collect::<Vec<_>>(),
+            [
+                ResourceType::A,
+                ResourceType::MX,
+                ResourceType::RRSIG,
+                ResourceType::NSEC,
+                ResourceType::Unknown(1234)
+            ]
+        );
+        assert_eq!(types.to_string(), "A MX RRSIG NSEC TYPE1234");
+
+        let mut written = vec![];
+        types.write(&mut written);
+        assert_eq!(written, buf);
+    }
+
+    #[test]
+    fn reject_non_canonical_bit_maps() {
+        use ResourceDataError::{InvalidTypeBitmap, Truncated};
+
+        assert_eq!(TypeBitmap::parse(&[0, 0]), Err(InvalidTypeBitmap));
+        assert_eq!(TypeBitmap::parse(&[0, 2, 0x40, 0]), Err(InvalidTypeBitmap));
+        assert_eq!(
+            TypeBitmap::parse(&[1, 1, 0x40, 0, 1, 0x40]),
+            Err(InvalidTypeBitmap)
+        );
+        assert_eq!(TypeBitmap::parse(&[0, 2, 0x40]), Err(Truncated));
+    }
+}
+
+#[cfg(test)]
+mod timestamp {
+    use super::*;
+
+    #[test]
+    fn round_trip_timestamps() {
+        for (timestamp, text) in [
+            (0, "19700101000000"),
+            (1_082_200_000, "20040417110640"),
+            (951_782_400, "20000229000000"),
+            (u32::MAX, "21060207062815"),
+        ] {
+            let mut written = String::new();
+            write_timestamp(&mut written, timestamp).unwrap();
+            assert_eq!(written, text);
+            assert_eq!(parse_timestamp(text), Some(timestamp));
+        }
+
+        assert_eq!(parse_timestamp("1082200000"), Some(1_082_200_000));
+        assert_eq!(parse_timestamp("20041317110640"), None);
+        assert_eq!(parse_timestamp("21060207062816"), None);
+    }
+}
+
+#[cfg(test)]
+mod records {
+    use super::*;
+    use crate::message::Label;
+
+    fn round_trip(data: ResourceData, text: &str) {
+        assert_eq!(data.to_string(), text);
+
+        let buf = Vec::try_from(data.clone()).unwrap();
+        assert_eq!(ResourceData::parse(data.typ(), &buf), Ok(data));
+    }
+
+    #[test]
+    fn round_trip_dns_key() {
+        round_trip(
+            ResourceData::DnsKey {
+                flags: 257,
+                protocol: 3,
+                algorithm: 8,
+                public_key: b"foobar".to_vec(),
+            },
+            "257 3 8 Zm9vYmFy",
+        );
+    }
+
+    #[test]
+    fn round_trip_signature() {
+        round_trip(
+            ResourceData::Signature {
+                type_covered: ResourceType::A,
+                algorithm: 8,
+                labels: 2,
+                original_time_to_live: 3600,
+                expiration: 1_082_200_000,
+                inception: 1_079_608_000,
+                key_tag: 2642,
+                signer: Label::parse_str("example.com").unwrap(),
+                signature: b"foob".to_vec(),
+            },
+            "A 8 2 3600 20040417110640 20040318110640 2642 example.com. Zm9vYg==",
+        );
+    }
+
+    #[test]
+    fn round_trip_delegation_signer() {
+        round_trip(
+            ResourceData::DelegationSigner {
+                key_tag: 60485,
+                algorithm: 5,
+                digest_type: 1,
+                digest: vec![0x2b, 0xb1, 0x83, 0xaf],
+            },
+            "60485 5 1 2bb183af",
+        );
+    }
+
+    #[test]
+    fn round_trip_next_secure() {
+        round_trip(
+            ResourceData::NextSecure {
+                next: Label::parse_str("host.example.com").unwrap(),
+                types: [ResourceType::A, ResourceType::RRSIG, ResourceType::NSEC]
+                    .into_iter()
+                    .collect(),
+            },
+            "host.example.com. A RRSIG NSEC",
+        );
+    }
+
+    #[test]
+    fn round_trip_hashed_next_secure() {
+        round_trip(
+            ResourceData::HashedNextSecure {
+                hash_algorithm: 1,
+                flags: 1,
+                iterations: 12,
+                salt: vec![0xaa, 0xbb, 0xcc, 0xdd],
+                next_hashed: b"foobar".to_vec(),
+                types: [ResourceType::MX, ResourceType::DNSKEY]
+                    .into_iter()
+                    .collect(),
+            },
+            "1 1 12 aabbccdd cpnmuoj1e8 MX DNSKEY",
+        );
+
+        round_trip(
+            ResourceData::HashedNextSecureParameters {
+                hash_algorithm: 1,
+                flags: 0,
+                iterations: 0,
+                salt: vec![],
+            },
+            "1 0 0 -",
+        );
+    }
+
+    #[test]
+    fn hash_fields_fit_their_length() {
+        let salt = "aa".repeat(256);
+        assert_eq!(
+            format!("NSEC3PARAM 1 0 0 {salt}").parse::<ResourceData>(),
+            Err(ResourceDataError::Syntax(salt))
+        );
+
+        let next_hashed = "cpnmuoj1".repeat(52);
+        assert_eq!(
+            format!("NSEC3 1 1 12 - {next_hashed} A").parse::<ResourceData>(),
+            Err(ResourceDataError::Syntax(next_hashed))
+        );
+
+        // built data isn't cut short when written
+        let data = ResourceData::HashedNextSecureParameters {
+            hash_algorithm: 1,
+            flags: 0,
+            iterations: 0,
+            salt: vec![0xaa; 256],
+        };
+        assert_eq!(
+            Vec::try_from(data),
+            Err(ResourceDataError::FieldTooLong {
+                max: 255,
+                found: 256
+            })
+        );
+    }
+
+    #[test]
+    fn timestamps_are_dates() {
+        assert_eq!(parse_timestamp("20240229000000"), Some(1_709_164_800));
+        assert_eq!(parse_timestamp("20230229000000"), None);
+        assert_eq!(parse_timestamp("20000230000000"), None);
+        assert_eq!(parse_timestamp("20040431000000"), None);
+        assert_eq!(parse_timestamp("20040430235959"), Some(1_083_369_599));
+        assert_eq!(parse_timestamp("20040430235960"), None);
+    }
+
+    #[test]
+    fn truncated_salt() {
+        assert_eq!(
+            ResourceData::parse(ResourceType::NSEC3PARAM, &[1, 0, 0, 12, 4, 0xaa]),
+            Err(ResourceDataError::Truncated)
+        );
+    }
+}
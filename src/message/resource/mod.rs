@@ -31,18 +31,26 @@
 //! [`resource records`]: ResourceRecord
 //! [`header`]: super::header::Header
 
-// TODO: use a safer parsing (i.e. check for buffer boundaries)
+pub mod dnssec;
+pub mod presentation;
+pub mod svcb;
+pub mod wks;
 
-use bytes::{Buf, BufMut};
+pub use dnssec::*;
+pub use presentation::*;
+pub use svcb::*;
+pub use wks::*;
 
 use super::{
-    parse_character_string, parse_label, CharacterString, Label, LabelError, ResourceClass,
-    ResourceType, UnregisteredClass, UnregisteredType,
+    edns::EdnsOption,
+    encoding::{decode_hex, write_base32hex, write_base64, write_hex, write_quoted},
+    expand_label, parse_character_string, parse_label, CharacterString, Counter, Label,
+    LabelCompressor, LabelError, ResourceClass, ResourceType, Sink,
 };
 use std::{
     error::Error,
     fmt::{self, Display},
-    net::Ipv4Addr,
+    net::{Ipv4Addr, Ipv6Addr},
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -67,21 +75,49 @@ impl ResourceRecord {
     }
 }
 
-impl From<ResourceRecord> for Vec<u8> {
-    fn from(value: ResourceRecord) -> Self {
-        let mut buf = vec![];
-        let data = value.data;
+impl ResourceRecord {
+    /// Appends the wire format of the record to `buf`, see [`Label::write`].
+    ///
+    /// Fails when the data is longer than the `u16::MAX` octets its RDLENGTH can count, or can't
+    /// be written on its own, leaving `buf` holding the beginning of the record.
+    pub fn write<'a>(
+        &'a self,
+        buf: &mut impl Sink,
+        mut names: Option<&mut LabelCompressor<'a>>,
+    ) -> Result<(), ResourceDataError> {
+        self.name.write(buf, names.as_deref_mut());
+        buf.put_u16(self.typ().into());
+        buf.put_u16(self.class.into());
+        buf.put_u32(self.time_to_live);
+
+        // the data is measured where it's written, and the names it remembered are forgotten
+        // until it's written for real
+        let start = buf.offset() + 2;
+        let mut length = Counter::starting_at(start);
+        let mark = names.as_deref().map(LabelCompressor::mark);
+        self.data.write(&mut length, names.as_deref_mut())?;
+        if let (Some(names), Some(mark)) = (names.as_deref_mut(), mark) {
+            names.forget(mark);
+        }
 
-        buf.extend::<Vec<_>>(value.name.into());
-        buf.put_u16(data.typ() as u16);
-        buf.put_u16(value.class as u16);
-        buf.put_u32(value.time_to_live);
+        let length = length.offset() - start;
+        buf.put_u16(
+            u16::try_from(length).map_err(|_| ResourceDataError::FieldTooLong {
+                max: u16::MAX as usize,
+                found: length,
+            })?,
+        );
+        self.data.write(buf, names)
+    }
+}
 
-        let data: Vec<u8> = data.into();
-        buf.put_u16(data.len() as u16);
-        buf.extend(data);
+impl TryFrom<ResourceRecord> for Vec<u8> {
+    type Error = ResourceDataError;
 
-        buf
+    fn try_from(value: ResourceRecord) -> Result<Self, Self::Error> {
+        let mut buf = vec![];
+        value.write(&mut buf, None)?;
+        Ok(buf)
     }
 }
 
@@ -198,30 +234,30 @@ pub enum ResourceData {
     /// files. NULLs are used as placeholders in some experimental extensions of the DNS.
     Null(Vec<u8>),
 
-    // (WKS) The WKS record is used to describe the well known services supported by a particular
-    // protocol on a particular internet address.  The PROTOCOL field specifies an IP protocol
-    // number, and the bit map has one bit per port of the specified protocol.  The first bit
-    // corresponds to port 0, the second to port 1, etc.  If the bit map does not include a bit for
-    // a protocol of interest, that bit is assumed zero.  The appropriate values and mnemonics for
-    // ports and protocols are specified in RFC-1010.
-    //
-    // For example, if PROTOCOL=TCP (6), the 26th bit corresponds to TCP port
-    // 25 (SMTP).  If this bit is set, a SMTP server should be listening on TCP port 25; if zero,
-    //    SMTP service is not supported on the specified address.
-    //
-    // The purpose of WKS RRs is to provide availability information for servers for TCP and UDP.
-    // If a server supports both TCP and UDP, or has multiple Internet addresses, then multiple WKS
-    // RRs are used.
-    //
-    // WKS RRs cause no additional section processing.
-    //
-    // In master files, both ports and protocols are expressed using mnemonics or decimal numbers.
+    /// (WKS) The WKS record is used to describe the well known services supported by a particular
+    /// protocol on a particular internet address.  The PROTOCOL field specifies an IP protocol
+    /// number, and the bit map has one bit per port of the specified protocol.  The first bit
+    /// corresponds to port 0, the second to port 1, etc.  If the bit map does not include a bit for
+    /// a protocol of interest, that bit is assumed zero.  The appropriate values and mnemonics for
+    /// ports and protocols are specified in RFC-1010.
+    ///
+    /// For example, if PROTOCOL=TCP (6), the 26th bit corresponds to TCP port
+    /// 25 (SMTP).  If this bit is set, a SMTP server should be listening on TCP port 25; if zero,
+    /// SMTP service is not supported on the specified address.
+    ///
+    /// The purpose of WKS RRs is to provide availability information for servers for TCP and UDP.
+    /// If a server supports both TCP and UDP, or has multiple Internet addresses, then multiple WKS
+    /// RRs are used.
+    ///
+    /// WKS RRs cause no additional section processing.
+    ///
+    /// In master files, both ports and protocols are expressed using mnemonics or decimal numbers.
     WKS {
         address: Ipv4Addr,
         /// IP protocol number
         protocol: u8,
-        /// TODO: implement bit map
-        bit_map: (),
+        /// The ports of the protocol with a listening server
+        bit_map: PortBitmap,
     },
 
     /// (PTR) A domain name which points to some locaiton in the domain name space.
@@ -278,6 +314,295 @@ pub enum ResourceData {
     /// TXT RRs are usedto hold descriptive text. The semantics of the text depends on the domain
     /// where it is found.
     Text(Vec<CharacterString>),
+
+    /// (AAAA) A 128 bit IPv6 address ([RFC3596]).
+    ///
+    /// Hosts that have multiple IPv6 addresses will have multiple AAAA records.
+    ///
+    /// AAAA records cause no additional section processing. The RDATA section of an AAAA line in
+    /// a master file is an IPv6 address in the text representation of [RFC4291 2.2] (e.g.,
+    /// "2001:db8::1").
+    ///
+    /// [RFC3596]: <https://datatracker.ietf.org/doc/html/rfc3596>
+    /// [RFC4291 2.2]: <https://datatracker.ietf.org/doc/html/rfc4291#section-2.2>
+    Ipv6Address(Ipv6Addr),
+
+    /// (SRV) The location of the server(s) for a specific protocol and domain ([RFC2782]).
+    ///
+    /// The owner name is of the form `_Service._Proto.Name`, e.g. `_ldap._tcp.example.com`. SRV
+    /// records cause type A and AAAA additional section processing for the host specified by
+    /// TARGET. A TARGET of `.` means that the service is decidedly not available at this domain.
+    ///
+    /// [RFC2782]: <https://datatracker.ietf.org/doc/html/rfc2782>
+    Service {
+        /// The priority of this target host, clients must attempt to contact the target host with
+        /// the lowest-numbered priority they can reach.
+        priority: u16,
+
+        /// A relative weight for entries with the same priority, larger weights should be given a
+        /// proportionately higher probability of being selected.
+        weight: u16,
+
+        /// The port on this target host of this service.
+        port: u16,
+
+        /// The domain name of the target host, which is never compressed.
+        target: Label,
+    },
+
+    /// (NAPTR) A rule rewriting a string into a domain name or URI, as part of a Dynamic
+    /// Delegation Discovery System application ([RFC3403 4]).
+    ///
+    /// Only one of `regexp` and `replacement` may be used: an empty `regexp` or a `replacement` of
+    /// `.`.
+    ///
+    /// [RFC3403 4]: <https://datatracker.ietf.org/doc/html/rfc3403#section-4>
+    NamingAuthorityPointer {
+        /// The order in which the records must be processed, lowest first.
+        order: u16,
+
+        /// The order in which records with equal `order` should be processed, lowest first.
+        preference: u16,
+
+        /// Control the rewriting and interpretation of the fields, e.g. `S`, `A`, `U` or `P`.
+        flags: CharacterString,
+
+        /// The service parameters applicable to this delegation path.
+        services: CharacterString,
+
+        /// A substitution expression applied to the original string.
+        regexp: CharacterString,
+
+        /// The next domain name to query for, which is never compressed.
+        replacement: Label,
+    },
+
+    /// (OPT) The options of an EDNS(0) pseudo record ([RFC6891]).
+    ///
+    /// OPT records are only found in the additional section of a message, and are lifted into
+    /// [`Message::edns`][super::Message::edns] when it's parsed. They're not allowed in master
+    /// files.
+    ///
+    /// [RFC6891]: <https://datatracker.ietf.org/doc/html/rfc6891>
+    Opt(Vec<EdnsOption>),
+
+    /// (DS) A reference to a DNSKEY record of a delegated zone, held by its parent ([RFC4034 5]).
+    ///
+    /// [RFC4034 5]: <https://datatracker.ietf.org/doc/html/rfc4034#section-5>
+    DelegationSigner {
+        /// The key tag of the DNSKEY record referred to.
+        key_tag: u16,
+
+        /// The algorithm number of the DNSKEY record referred to.
+        algorithm: u8,
+
+        /// The algorithm used to construct the digest.
+        digest_type: u8,
+
+        /// A digest of the owner name and data of the DNSKEY record referred to.
+        digest: Vec<u8>,
+    },
+
+    /// (SSHFP) The fingerprint of the SSH public key of the owner ([RFC4255 3]).
+    ///
+    /// [RFC4255 3]: <https://datatracker.ietf.org/doc/html/rfc4255#section-3>
+    SshFingerprint {
+        /// The algorithm of the public key, e.g. 1 for RSA or 4 for Ed25519.
+        algorithm: u8,
+
+        /// The message digest algorithm used to calculate the fingerprint, e.g. 2 for SHA-256.
+        fingerprint_type: u8,
+
+        fingerprint: Vec<u8>,
+    },
+
+    /// (RRSIG) A digital signature over the RRset of the owner name, class and covered type
+    /// ([RFC4034 3]).
+    ///
+    /// [RFC4034 3]: <https://datatracker.ietf.org/doc/html/rfc4034#section-3>
+    Signature {
+        /// The type of the RRset covered by this signature.
+        type_covered: ResourceType,
+
+        /// The cryptographic algorithm used to create the signature.
+        algorithm: u8,
+
+        /// The number of labels in the original owner name, not counting the root nor a leading
+        /// wildcard label.
+        labels: u8,
+
+        /// The TTL of the covered RRset as it appears in the authoritative zone.
+        original_time_to_live: u32,
+
+        /// The signature must not be used for authentication after this time, in seconds since
+        /// the epoch (using serial number arithmetic).
+        expiration: u32,
+
+        /// The signature must not be used for authentication prior to this time.
+        inception: u32,
+
+        /// The key tag of the DNSKEY record that validates this signature.
+        key_tag: u16,
+
+        /// The owner name of the DNSKEY record that validates this signature, never compressed.
+        signer: Label,
+
+        /// The cryptographic signature.
+        signature: Vec<u8>,
+    },
+
+    /// (NSEC) The next owner name in the canonical ordering of the zone, and the types present at
+    /// the owner name ([RFC4034 4]).
+    ///
+    /// [RFC4034 4]: <https://datatracker.ietf.org/doc/html/rfc4034#section-4>
+    NextSecure {
+        /// The next owner name that has authoritative data or a delegation point, never compressed.
+        next: Label,
+
+        /// The types present at the owner name.
+        types: TypeBitmap,
+    },
+
+    /// (DNSKEY) A public key used to verify the signatures of a zone ([RFC4034 2]).
+    ///
+    /// [RFC4034 2]: <https://datatracker.ietf.org/doc/html/rfc4034#section-2>
+    DnsKey {
+        /// Bit 7 is the zone key flag, and bit 15 is the secure entry point flag.
+        flags: u16,
+
+        /// Must be 3.
+        protocol: u8,
+
+        /// The public key's cryptographic algorithm.
+        algorithm: u8,
+
+        /// The public key material, whose format depends on the algorithm.
+        public_key: Vec<u8>,
+    },
+
+    /// (NSEC3) The hashed next owner name in the hash order of the zone, and the types present at
+    /// the owner name ([RFC5155 3]).
+    ///
+    /// [RFC5155 3]: <https://datatracker.ietf.org/doc/html/rfc5155#section-3>
+    HashedNextSecure {
+        /// The cryptographic hash algorithm used to construct the hash.
+        hash_algorithm: u8,
+
+        /// Bit 7 is the opt-out flag.
+        flags: u8,
+
+        /// The number of additional times the hash function has been performed.
+        iterations: u16,
+
+        /// Appended to the original owner name before hashing, at most 255 octets.
+        salt: Vec<u8>,
+
+        /// The next hashed owner name, in binary form, at most 255 octets.
+        next_hashed: Vec<u8>,
+
+        /// The types present at the original owner name.
+        types: TypeBitmap,
+    },
+
+    /// (NSEC3PARAM) The parameters an authoritative server needs to calculate hashed owner names
+    /// ([RFC5155 4]).
+    ///
+    /// [RFC5155 4]: <https://datatracker.ietf.org/doc/html/rfc5155#section-4>
+    HashedNextSecureParameters {
+        /// The cryptographic hash algorithm used to construct the hash.
+        hash_algorithm: u8,
+
+        /// Must be zero.
+        flags: u8,
+
+        /// The number of additional times the hash function has been performed.
+        iterations: u16,
+
+        /// Appended to the original owner name before hashing, at most 255 octets.
+        salt: Vec<u8>,
+    },
+
+    /// (TLSA) The certificate or public key of a TLS server, associated with the domain name
+    /// `_<port>._<protocol>.<host>` ([RFC6698 2]).
+    ///
+    /// [RFC6698 2]: <https://datatracker.ietf.org/doc/html/rfc6698#section-2>
+    TlsAssociation {
+        /// How the association is matched against the certificate chain presented by the server.
+        usage: u8,
+
+        /// Whether the full certificate (0) or its public key (1) is matched.
+        selector: u8,
+
+        /// Whether the association data is presented exactly (0), or hashed with SHA-256 (1) or
+        /// SHA-512 (2).
+        matching_type: u8,
+
+        /// The certificate association data to be matched.
+        data: Vec<u8>,
+    },
+
+    /// (SVCB) The alternative endpoint of a service and the parameters needed to reach it
+    /// ([RFC9460 2]).
+    ///
+    /// A priority of 0 puts the record in alias mode, where the target is an alias of the owner
+    /// and there are no parameters. Otherwise the record is in service mode, and a target of `.`
+    /// stands for the owner name itself.
+    ///
+    /// [RFC9460 2]: <https://datatracker.ietf.org/doc/html/rfc9460#section-2>
+    ServiceBinding {
+        /// Endpoints with lower priorities are preferred.
+        priority: u16,
+
+        /// The domain name of the endpoint, never compressed.
+        target: Label,
+
+        params: SvcParams,
+    },
+
+    /// (HTTPS) A [`ServiceBinding`][ResourceData::ServiceBinding] for the `https` scheme, whose
+    /// owner is the origin hostname ([RFC9460 9]).
+    ///
+    /// [RFC9460 9]: <https://datatracker.ietf.org/doc/html/rfc9460#section-9>
+    HttpsServiceBinding {
+        /// Endpoints with lower priorities are preferred.
+        priority: u16,
+
+        /// The domain name of the endpoint, never compressed.
+        target: Label,
+
+        params: SvcParams,
+    },
+
+    /// (CAA) A property restricting the certification authorities that may issue certificates
+    /// for the owner ([RFC8659 4]).
+    ///
+    /// [RFC8659 4]: <https://datatracker.ietf.org/doc/html/rfc8659#section-4>
+    CertificationAuthority {
+        /// Bit 0 is the issuer critical flag, a certification authority must not issue
+        /// certificates when it doesn't understand a property that has it set.
+        flags: u8,
+
+        /// The property identifier, e.g. `issue`, `issuewild` or `iodef`, made of ASCII letters
+        /// and digits only.
+        tag: String,
+
+        /// The property value, whose format depends on the tag.
+        value: Vec<u8>,
+    },
+
+    /// The opaque data of a type that is not known to this implementation ([RFC3597]).
+    ///
+    /// The data is kept exactly as it was received, so that the record can be passed on unchanged.
+    /// Its presentation format is `\# <length> <hex>`.
+    ///
+    /// [RFC3597]: <https://datatracker.ietf.org/doc/html/rfc3597>
+    Unknown { typ: u16, data: Vec<u8> },
+
+    /// The empty data of the records of class ANY or NONE of dynamic updates, which only name the
+    /// type of the RRset they refer to ([RFC2136 2.4]). Its presentation format is `\# 0`.
+    ///
+    /// [RFC2136 2.4]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2.4>
+    Empty(ResourceType),
 }
 
 impl ResourceData {
@@ -300,43 +625,123 @@ impl ResourceData {
             ResourceData::MailInfo { .. } => MINFO,
             ResourceData::MailExchange { .. } => MX,
             ResourceData::Text(_) => TXT,
+            ResourceData::Ipv6Address(_) => AAAA,
+            ResourceData::Service { .. } => SRV,
+            ResourceData::NamingAuthorityPointer { .. } => NAPTR,
+            ResourceData::SshFingerprint { .. } => SSHFP,
+            ResourceData::TlsAssociation { .. } => TLSA,
+            ResourceData::CertificationAuthority { .. } => CAA,
+            ResourceData::Opt(_) => OPT,
+            ResourceData::DelegationSigner { .. } => DS,
+            ResourceData::Signature { .. } => RRSIG,
+            ResourceData::NextSecure { .. } => NSEC,
+            ResourceData::DnsKey { .. } => DNSKEY,
+            ResourceData::HashedNextSecure { .. } => NSEC3,
+            ResourceData::HashedNextSecureParameters { .. } => NSEC3PARAM,
+            ResourceData::ServiceBinding { .. } => SVCB,
+            ResourceData::HttpsServiceBinding { .. } => HTTPS,
+            ResourceData::Unknown { typ, .. } => (*typ).into(),
+            ResourceData::Empty(typ) => *typ,
         }
     }
 }
 
-impl From<ResourceData> for Vec<u8> {
-    fn from(value: ResourceData) -> Self {
+impl ResourceData {
+    /// The domain name of the host whose addresses should be included in the additional section
+    /// of a response carrying this data.
+    pub fn additional_name(&self) -> Option<&Label> {
         use ResourceData::*;
-        let mut buf = vec![];
+        match self {
+            NameServer(name) | MailDevice(name) | MailForward(name) | MailBox(name) => Some(name),
+            MailExchange { exchange, .. } => Some(exchange),
+            Service { target, .. }
+            | ServiceBinding { target, .. }
+            | HttpsServiceBinding { target, .. }
+                if target.domain_count() > 0 =>
+            {
+                Some(target)
+            }
+            _ => None,
+        }
+    }
+
+    /// Replaces the compression pointers ending the domain names of the data by the labels they
+    /// refer to inside of `message`, see [`expand_label`], which should end with the data so that
+    /// pointers past it are rejected.
+    pub fn expand_names(&mut self, message: &[u8]) -> Result<(), LabelError> {
+        use ResourceData::*;
+        match self {
+            CanonicalName(name) | MailDevice(name) | MailRename(name) | MailForward(name)
+            | MailBox(name) | MailGroup(name) | NameServer(name) | Ptr(name) => {
+                expand_label(name, message)
+            }
+            MailInfo {
+                mailbox,
+                error_mailbox,
+            } => {
+                expand_label(mailbox, message)?;
+                expand_label(error_mailbox, message)
+            }
+            SOA { name, mail, .. } => {
+                expand_label(name, message)?;
+                expand_label(mail, message)
+            }
+            MailExchange { exchange, .. } => expand_label(exchange, message),
+            Service { target, .. }
+            | ServiceBinding { target, .. }
+            | HttpsServiceBinding { target, .. } => expand_label(target, message),
+            NamingAuthorityPointer { replacement, .. } => expand_label(replacement, message),
+            Signature { signer, .. } => expand_label(signer, message),
+            NextSecure { next, .. } => expand_label(next, message),
+            _ => Ok(()),
+        }
+    }
 
-        match value {
+    /// Appends the wire format of the data to `buf`, see [`Label::write`].
+    ///
+    /// Only the domain names of the types defined in [RFC1035] are compressed, as other
+    /// implementations might not know how to decompress the rest ([RFC3597 4]). Their suffixes
+    /// are remembered as well, so that the names written after the data may point to them.
+    ///
+    /// [RFC1035]: <https://datatracker.ietf.org/doc/html/rfc1035#section-3.3>
+    /// [RFC3597 4]: <https://datatracker.ietf.org/doc/html/rfc3597#section-4>
+    pub fn write<'a>(
+        &'a self,
+        buf: &mut impl Sink,
+        mut names: Option<&mut LabelCompressor<'a>>,
+    ) -> Result<(), ResourceDataError> {
+        use ResourceData::*;
+
+        match self {
             HostInfo { cpu, os } => {
-                buf.extend::<Vec<_>>(cpu.into());
-                buf.extend::<Vec<_>>(os.into());
+                cpu.write(buf);
+                os.write(buf);
             }
 
             CanonicalName(name) | MailDevice(name) | MailRename(name) | MailForward(name)
             | MailBox(name) | MailGroup(name) | NameServer(name) | Ptr(name) => {
-                buf.extend::<Vec<_>>(name.into())
+                name.write(buf, names.as_deref_mut())
             }
 
             MailInfo {
                 mailbox,
                 error_mailbox,
             } => {
-                buf.extend::<Vec<_>>(mailbox.into());
-                buf.extend::<Vec<_>>(error_mailbox.into());
+                mailbox.write(buf, names.as_deref_mut());
+                error_mailbox.write(buf, names.as_deref_mut());
             }
 
             MailExchange {
                 preference,
                 exchange,
             } => {
-                buf.put_u16(preference);
-                buf.extend::<Vec<_>>(exchange.into());
+                buf.put_u16(*preference);
+                exchange.write(buf, names.as_deref_mut());
             }
 
-            Null(bytes) => buf.extend(bytes),
+            Null(bytes) | Unknown { data: bytes, .. } => buf.put_slice(bytes),
+
+            Empty(_) => (),
 
             SOA {
                 name,
@@ -347,39 +752,503 @@ impl From<ResourceData> for Vec<u8> {
                 expire,
                 minimum,
             } => {
-                buf.extend::<Vec<_>>(name.into());
-                buf.extend::<Vec<_>>(mail.into());
-                buf.put_u32(serial);
-                buf.put_u32(refresh);
-                buf.put_u32(retry);
-                buf.put_u32(expire);
-                buf.put_u32(minimum);
+                name.write(buf, names.as_deref_mut());
+                mail.write(buf, names);
+                buf.put_u32(*serial);
+                buf.put_u32(*refresh);
+                buf.put_u32(*retry);
+                buf.put_u32(*expire);
+                buf.put_u32(*minimum);
             }
 
             Text(text) => {
                 for word in text {
-                    buf.extend::<Vec<_>>(word.into());
+                    word.write(buf);
                 }
             }
 
-            Address(ip) => buf.put_u32(ip.into()),
+            Address(ip) => buf.put_u32((*ip).into()),
+
+            Ipv6Address(ip) => buf.put_u128((*ip).into()),
+
+            Service {
+                priority,
+                weight,
+                port,
+                target,
+            } => {
+                buf.put_u16(*priority);
+                buf.put_u16(*weight);
+                buf.put_u16(*port);
+                target.write(buf, None);
+            }
+
+            NamingAuthorityPointer {
+                order,
+                preference,
+                flags,
+                services,
+                regexp,
+                replacement,
+            } => {
+                buf.put_u16(*order);
+                buf.put_u16(*preference);
+                flags.write(buf);
+                services.write(buf);
+                regexp.write(buf);
+                replacement.write(buf, None);
+            }
+
+            SshFingerprint {
+                algorithm,
+                fingerprint_type,
+                fingerprint,
+            } => {
+                buf.put_u8(*algorithm);
+                buf.put_u8(*fingerprint_type);
+                buf.put_slice(fingerprint);
+            }
+
+            TlsAssociation {
+                usage,
+                selector,
+                matching_type,
+                data,
+            } => {
+                buf.put_u8(*usage);
+                buf.put_u8(*selector);
+                buf.put_u8(*matching_type);
+                buf.put_slice(data);
+            }
+
+            CertificationAuthority { flags, tag, value } => {
+                buf.put_u8(*flags);
+                write_short(tag.as_bytes(), buf)?;
+                buf.put_slice(value);
+            }
+
+            Opt(options) => EdnsOption::write_all(options, buf)?,
+
+            DelegationSigner {
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+            } => {
+                buf.put_u16(*key_tag);
+                buf.put_u8(*algorithm);
+                buf.put_u8(*digest_type);
+                buf.put_slice(digest);
+            }
+
+            Signature {
+                type_covered,
+                algorithm,
+                labels,
+                original_time_to_live,
+                expiration,
+                inception,
+                key_tag,
+                signer,
+                signature,
+            } => {
+                buf.put_u16((*type_covered).into());
+                buf.put_u8(*algorithm);
+                buf.put_u8(*labels);
+                buf.put_u32(*original_time_to_live);
+                buf.put_u32(*expiration);
+                buf.put_u32(*inception);
+                buf.put_u16(*key_tag);
+                signer.write(buf, None);
+                buf.put_slice(signature);
+            }
+
+            NextSecure { next, types } => {
+                next.write(buf, None);
+                types.write(buf);
+            }
+
+            DnsKey {
+                flags,
+                protocol,
+                algorithm,
+                public_key,
+            } => {
+                buf.put_u16(*flags);
+                buf.put_u8(*protocol);
+                buf.put_u8(*algorithm);
+                buf.put_slice(public_key);
+            }
+
+            HashedNextSecure {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+                next_hashed,
+                types,
+            } => {
+                buf.put_u8(*hash_algorithm);
+                buf.put_u8(*flags);
+                buf.put_u16(*iterations);
+                write_short(salt, buf)?;
+                write_short(next_hashed, buf)?;
+                types.write(buf);
+            }
+
+            HashedNextSecureParameters {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+            } => {
+                buf.put_u8(*hash_algorithm);
+                buf.put_u8(*flags);
+                buf.put_u16(*iterations);
+                write_short(salt, buf)?;
+            }
+
+            ServiceBinding {
+                priority,
+                target,
+                params,
+            }
+            | HttpsServiceBinding {
+                priority,
+                target,
+                params,
+            } => {
+                buf.put_u16(*priority);
+                target.write(buf, None);
+                params.write(buf)?;
+            }
 
-            WKS { .. } => todo!("implement the WKS serialization"),
+            WKS {
+                address,
+                protocol,
+                bit_map,
+            } => {
+                buf.put_u32((*address).into());
+                buf.put_u8(*protocol);
+                buf.put_slice(bit_map.as_bytes());
+            }
         }
 
-        buf
+        Ok(())
+    }
+}
+
+impl Display for ResourceData {
+    /// Presents the data in the master file format of its type.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.present(f, &Label::default())
+    }
+}
+
+/// Data presented with its names relative to an origin, see [`ResourceData::relative_to`].
+#[derive(Debug, Clone, Copy)]
+pub struct RelativeData<'a> {
+    data: &'a ResourceData,
+    origin: &'a Label,
+}
+
+impl Display for RelativeData<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.data.present(f, self.origin)
+    }
+}
+
+impl ResourceData {
+    /// Presents the data in the master file format of its type, with its names relative to
+    /// `origin` (see [`Label::relative_to`]).
+    pub fn relative_to<'a>(&'a self, origin: &'a Label) -> RelativeData<'a> {
+        RelativeData { data: self, origin }
+    }
+
+    fn present(&self, f: &mut fmt::Formatter<'_>, origin: &Label) -> fmt::Result {
+        use ResourceData::*;
+        match self {
+            Address(ip) => ip.fmt(f),
+
+            Ipv6Address(ip) => ip.fmt(f),
+
+            Service {
+                priority,
+                weight,
+                port,
+                target,
+            } => write!(
+                f,
+                "{priority} {weight} {port} {}",
+                target.relative_to(origin)
+            ),
+
+            NamingAuthorityPointer {
+                order,
+                preference,
+                flags,
+                services,
+                regexp,
+                replacement,
+            } => write!(
+                f,
+                "{order} {preference} {flags} {services} {regexp} {}",
+                replacement.relative_to(origin)
+            ),
+
+            SshFingerprint {
+                algorithm,
+                fingerprint_type,
+                fingerprint,
+            } => {
+                write!(f, "{algorithm} {fingerprint_type} ")?;
+                write_hex(f, fingerprint)
+            }
+
+            TlsAssociation {
+                usage,
+                selector,
+                matching_type,
+                data,
+            } => {
+                write!(f, "{usage} {selector} {matching_type} ")?;
+                write_hex(f, data)
+            }
+
+            CertificationAuthority { flags, tag, value } => {
+                write!(f, "{flags} {tag} ")?;
+                write_quoted(f, value)
+            }
+
+            CanonicalName(name) | MailDevice(name) | MailRename(name) | MailForward(name)
+            | MailBox(name) | MailGroup(name) | NameServer(name) | Ptr(name) => {
+                name.relative_to(origin).fmt(f)
+            }
+
+            SOA {
+                name,
+                mail,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            } => write!(
+                f,
+                "{} {} {serial} {refresh} {retry} {expire} {minimum}",
+                name.relative_to(origin),
+                mail.relative_to(origin)
+            ),
+
+            WKS {
+                address,
+                protocol,
+                bit_map,
+            } => {
+                write!(f, "{address} ")?;
+                match protocol_name(*protocol) {
+                    Some(name) => name.fmt(f)?,
+                    None => protocol.fmt(f)?,
+                }
+                match bit_map.ports().next() {
+                    Some(_) => write!(f, " {bit_map}"),
+                    None => Ok(()),
+                }
+            }
+
+            HostInfo { cpu, os } => write!(f, "{cpu} {os}"),
+
+            MailInfo {
+                mailbox,
+                error_mailbox,
+            } => write!(
+                f,
+                "{} {}",
+                mailbox.relative_to(origin),
+                error_mailbox.relative_to(origin)
+            ),
+
+            MailExchange {
+                preference,
+                exchange,
+            } => write!(f, "{preference} {}", exchange.relative_to(origin)),
+
+            Text(text) => {
+                for (index, word) in text.iter().enumerate() {
+                    if index > 0 {
+                        " ".fmt(f)?;
+                    }
+                    word.fmt(f)?;
+                }
+                Ok(())
+            }
+
+            DelegationSigner {
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+            } => {
+                write!(f, "{key_tag} {algorithm} {digest_type} ")?;
+                write_hex(f, digest)
+            }
+
+            Signature {
+                type_covered,
+                algorithm,
+                labels,
+                original_time_to_live,
+                expiration,
+                inception,
+                key_tag,
+                signer,
+                signature,
+            } => {
+                write!(
+                    f,
+                    "{type_covered} {algorithm} {labels} {original_time_to_live} "
+                )?;
+                write_timestamp(f, *expiration)?;
+                " ".fmt(f)?;
+                write_timestamp(f, *inception)?;
+                write!(f, " {key_tag} {} ", signer.relative_to(origin))?;
+                write_base64(f, signature)
+            }
+
+            NextSecure { next, types } => match types.types().next() {
+                Some(_) => write!(f, "{} {types}", next.relative_to(origin)),
+                None => next.relative_to(origin).fmt(f),
+            },
+
+            DnsKey {
+                flags,
+                protocol,
+                algorithm,
+                public_key,
+            } => {
+                write!(f, "{flags} {protocol} {algorithm} ")?;
+                write_base64(f, public_key)
+            }
+
+            HashedNextSecure {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+                next_hashed,
+                types,
+            } => {
+                write!(f, "{hash_algorithm} {flags} {iterations} ")?;
+                ResourceData::write_salt(f, salt)?;
+                " ".fmt(f)?;
+                write_base32hex(f, next_hashed)?;
+                match types.types().next() {
+                    Some(_) => write!(f, " {types}"),
+                    None => Ok(()),
+                }
+            }
+
+            HashedNextSecureParameters {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+            } => {
+                write!(f, "{hash_algorithm} {flags} {iterations} ")?;
+                ResourceData::write_salt(f, salt)
+            }
+
+            ServiceBinding {
+                priority,
+                target,
+                params,
+            }
+            | HttpsServiceBinding {
+                priority,
+                target,
+                params,
+            } => match params.is_empty() {
+                true => write!(f, "{priority} {}", target.relative_to(origin)),
+                false => write!(f, "{priority} {} {params}", target.relative_to(origin)),
+            },
+
+            // NULL and OPT RRs are not allowed in master files
+            Null(data) | Unknown { data, .. } => ResourceData::write_generic(f, data),
+            Empty(_) => ResourceData::write_generic(f, &[]),
+            Opt(_) => {
+                let data = Vec::try_from(self.clone()).map_err(|_| fmt::Error)?;
+                ResourceData::write_generic(f, &data)
+            }
+        }
+    }
+}
+
+/// Appends `bytes` after their length octet, failing when they're longer than the 255 octets it
+/// can count.
+fn write_short(bytes: &[u8], buf: &mut impl Sink) -> Result<(), ResourceDataError> {
+    let length = u8::try_from(bytes.len()).map_err(|_| ResourceDataError::FieldTooLong {
+        max: u8::MAX as usize,
+        found: bytes.len(),
+    })?;
+    buf.put_u8(length);
+    buf.put_slice(bytes);
+    Ok(())
+}
+
+impl TryFrom<ResourceData> for Vec<u8> {
+    type Error = ResourceDataError;
+
+    fn try_from(value: ResourceData) -> Result<Self, Self::Error> {
+        let mut buf = vec![];
+        value.write(&mut buf, None)?;
+        Ok(buf)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResourceDataError {
     Label(LabelError),
+    /// The data ends before all of its fields were read
+    Truncated,
+    /// The data of a fixed size type doesn't have the expected length, or octets are left after
+    /// the last field of the data
+    InvalidLength {
+        expected: usize,
+        found: usize,
+    },
+    /// An unexpected token was found in the presentation format of the data
+    Syntax(String),
+    /// The window blocks of an NSEC type bit map are out of order, empty or padded
+    InvalidTypeBitmap,
+    /// A service parameter is out of order, repeated or has a malformed value
+    InvalidServiceParameter(SvcParamKey),
+    /// A field is longer than its length can count
+    FieldTooLong {
+        max: usize,
+        found: usize,
+    },
 }
 
 impl Display for ResourceDataError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ResourceDataError::*;
         match self {
-            ResourceDataError::Label(err) => err.fmt(f),
+            Label(err) => err.fmt(f),
+            Truncated => "resource data ends before all of its fields were read".fmt(f),
+            InvalidLength { expected, found } => {
+                format!("resource data must be of length '{expected}', but found '{found}'").fmt(f)
+            }
+            Syntax(token) => format!("unexpected '{token}' in resource data").fmt(f),
+            InvalidTypeBitmap => {
+                "type bit map windows must be ordered, non-empty and unpadded".fmt(f)
+            }
+            InvalidServiceParameter(key) => {
+                format!("service parameter '{key}' is out of order, repeated or malformed").fmt(f)
+            }
+            FieldTooLong { max, found } => format!(
+                "resource data field must be at most '{max}' octets long, but found '{found}'"
+            )
+            .fmt(f),
         }
     }
 }
@@ -392,16 +1261,134 @@ impl From<LabelError> for ResourceDataError {
     }
 }
 
+/// Splits the first `N` bytes off of `buf`, leaving it untouched when it's too short.
+pub(super) fn take<const N: usize>(buf: &mut &[u8]) -> Option<[u8; N]> {
+    let (bytes, rest) = buf.split_first_chunk::<N>()?;
+    *buf = rest;
+    Some(*bytes)
+}
+
 impl ResourceData {
+    /// Parses the wire format of the data of a record of type `typ` and class `class`.
+    ///
+    /// The records of class ANY or NONE of dynamic updates have an empty data whatever their type
+    /// ([RFC2136 2.4]), which is kept as [`Empty`][Self::Empty] data. The class of an OPT record
+    /// is a payload size instead, so its data is always parsed.
+    ///
+    /// [RFC2136 2.4]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2.4>
+    pub fn parse_in_class(
+        typ: ResourceType,
+        class: ResourceClass,
+        buf: &[u8],
+    ) -> Result<ResourceData, ResourceDataError> {
+        match (typ, class, buf) {
+            (ResourceType::OPT, ..) => ResourceData::parse(typ, buf),
+            (_, ResourceClass::ANY | ResourceClass::NONE, []) => Ok(ResourceData::Empty(typ)),
+            _ => ResourceData::parse(typ, buf),
+        }
+    }
+
+    /// Parses the wire format of the data of a record of type `typ`.
+    pub fn parse(typ: ResourceType, buf: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        use ResourceData::*;
+        Ok(match typ {
+            ResourceType::A => ResourceData::parse_address(buf)?,
+            ResourceType::NS => wrap_label(buf, NameServer)?,
+            ResourceType::MD => wrap_label(buf, MailDevice)?,
+            ResourceType::MF => wrap_label(buf, MailForward)?,
+            ResourceType::CNAME => wrap_label(buf, CanonicalName)?,
+            ResourceType::SOA => ResourceData::parse_soa(buf)?,
+            ResourceType::MB => wrap_label(buf, MailBox)?,
+            ResourceType::MG => wrap_label(buf, MailGroup)?,
+            ResourceType::MR => wrap_label(buf, MailRename)?,
+            ResourceType::NULL => Null(buf.to_vec()),
+            ResourceType::WKS => ResourceData::parse_well_known_services(buf)?,
+            ResourceType::PTR => wrap_label(buf, Ptr)?,
+            ResourceType::HINFO => ResourceData::parse_host_info(buf)?,
+            ResourceType::MINFO => ResourceData::parse_mail_info(buf)?,
+            ResourceType::MX => ResourceData::parse_mail_exchange(buf)?,
+            ResourceType::TXT => ResourceData::parse_text(buf)?,
+            ResourceType::AAAA => ResourceData::parse_ipv6_address(buf)?,
+            ResourceType::SRV => ResourceData::parse_service(buf)?,
+            ResourceType::NAPTR => ResourceData::parse_naming_authority_pointer(buf)?,
+            ResourceType::SSHFP => ResourceData::parse_ssh_fingerprint(buf)?,
+            ResourceType::TLSA => ResourceData::parse_tls_association(buf)?,
+            ResourceType::CAA => ResourceData::parse_certification_authority(buf)?,
+            ResourceType::OPT => ResourceData::parse_options(buf)?,
+            ResourceType::DS => ResourceData::parse_delegation_signer(buf)?,
+            ResourceType::RRSIG => ResourceData::parse_signature(buf)?,
+            ResourceType::NSEC => ResourceData::parse_next_secure(buf)?,
+            ResourceType::DNSKEY => ResourceData::parse_dns_key(buf)?,
+            ResourceType::NSEC3 => ResourceData::parse_hashed_next_secure(buf)?,
+            ResourceType::NSEC3PARAM => ResourceData::parse_hashed_next_secure_parameters(buf)?,
+            ResourceType::SVCB => ResourceData::parse_service_binding(buf, false)?,
+            ResourceType::HTTPS => ResourceData::parse_service_binding(buf, true)?,
+            typ @ (ResourceType::ANY | ResourceType::Unknown(_)) => Unknown {
+                typ: typ.into(),
+                data: buf.to_vec(),
+            },
+        })
+    }
+
+    /// Parses the generic `\# <length> <hex>` presentation format of [RFC3597 5], which may be
+    /// used for types that are known as well.
+    ///
+    /// [RFC3597 5]: <https://datatracker.ietf.org/doc/html/rfc3597#section-5>
+    pub fn from_generic(typ: ResourceType, text: &str) -> Result<ResourceData, ResourceDataError> {
+        use ResourceDataError::{InvalidLength, Syntax};
+
+        let text = text.trim_start();
+        let text = text
+            .strip_prefix("\\#")
+            .ok_or_else(|| Syntax(text.to_owned()))?;
+        let mut tokens = text.split_ascii_whitespace();
+
+        let length = tokens.next().ok_or_else(|| Syntax(text.to_owned()))?;
+        let length = length
+            .parse::<u16>()
+            .map_err(|_| Syntax(length.to_owned()))?;
+        let data =
+            decode_hex(&tokens.collect::<String>()).ok_or_else(|| Syntax(text.to_owned()))?;
+
+        if data.len() != length as usize {
+            return Err(InvalidLength {
+                expected: length as usize,
+                found: data.len(),
+            });
+        }
+
+        ResourceData::parse(typ, &data)
+    }
+
+    /// An empty salt is presented as `-`.
+    fn write_salt(f: &mut fmt::Formatter<'_>, salt: &[u8]) -> fmt::Result {
+        match salt.is_empty() {
+            true => "-".fmt(f),
+            false => write_hex(f, salt),
+        }
+    }
+
+    fn write_generic(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+        write!(f, "\\# {}", data.len())?;
+        if !data.is_empty() {
+            " ".fmt(f)?;
+            write_hex(f, data)?;
+        }
+        Ok(())
+    }
+
     fn parse_host_info(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
         let (cpu, offset) = parse_character_string(value)?;
-        let (os, _) = parse_character_string(&value[offset..])?;
+        let buf = &value[offset..];
+        let (os, offset) = parse_character_string(buf)?;
+        expect_end(value, &buf[offset..])?;
         Ok(Self::HostInfo { cpu, os })
     }
 
     fn parse_mail_exchange(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
-        let preference = u16::from_be_bytes(value[..2].try_into().unwrap());
-        let exchange = Label::try_from(&value[2..])?;
+        let mut buf = value;
+        let preference = u16::from_be_bytes(take(&mut buf).ok_or(ResourceDataError::Truncated)?);
+        let exchange = parse_last_label(value, buf)?;
         Ok(Self::MailExchange {
             preference,
             exchange,
@@ -409,13 +1396,130 @@ impl ResourceData {
     }
 
     fn parse_address(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
-        let ip = Ipv4Addr::from(u32::from_be_bytes(value[..4].try_into().unwrap()));
-        Ok(Self::Address(ip))
+        let octets: [u8; 4] = value
+            .try_into()
+            .map_err(|_| ResourceDataError::InvalidLength {
+                expected: 4,
+                found: value.len(),
+            })?;
+        Ok(Self::Address(Ipv4Addr::from(octets)))
+    }
+
+    fn parse_well_known_services(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let [a, b, c, d, protocol] = take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+        Ok(Self::WKS {
+            address: Ipv4Addr::new(a, b, c, d),
+            protocol,
+            bit_map: buf.to_vec().into(),
+        })
+    }
+
+    fn parse_ipv6_address(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let octets: [u8; 16] = value
+            .try_into()
+            .map_err(|_| ResourceDataError::InvalidLength {
+                expected: 16,
+                found: value.len(),
+            })?;
+        Ok(Self::Ipv6Address(Ipv6Addr::from(octets)))
+    }
+
+    fn parse_service(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let [p0, p1, w0, w1, n0, n1] = take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+        Ok(Self::Service {
+            priority: u16::from_be_bytes([p0, p1]),
+            weight: u16::from_be_bytes([w0, w1]),
+            port: u16::from_be_bytes([n0, n1]),
+            target: parse_last_label(value, buf)?,
+        })
+    }
+
+    fn parse_naming_authority_pointer(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let [o0, o1, p0, p1] = take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+
+        let (flags, offset) = parse_character_string(buf)?;
+        buf = &buf[offset..];
+        let (services, offset) = parse_character_string(buf)?;
+        buf = &buf[offset..];
+        let (regexp, offset) = parse_character_string(buf)?;
+        buf = &buf[offset..];
+
+        Ok(Self::NamingAuthorityPointer {
+            order: u16::from_be_bytes([o0, o1]),
+            preference: u16::from_be_bytes([p0, p1]),
+            flags,
+            services,
+            regexp,
+            replacement: parse_last_label(value, buf)?,
+        })
+    }
+
+    fn parse_ssh_fingerprint(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let [algorithm, fingerprint_type] = take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+        Ok(Self::SshFingerprint {
+            algorithm,
+            fingerprint_type,
+            fingerprint: buf.to_vec(),
+        })
+    }
+
+    fn parse_tls_association(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let [usage, selector, matching_type] =
+            take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+        Ok(Self::TlsAssociation {
+            usage,
+            selector,
+            matching_type,
+            data: buf.to_vec(),
+        })
+    }
+
+    fn parse_certification_authority(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let [flags, length] = take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+        let tag = buf
+            .get(..length as usize)
+            .ok_or(ResourceDataError::Truncated)?;
+
+        if tag.is_empty() || !tag.iter().all(u8::is_ascii_alphanumeric) {
+            let tag = String::from_utf8_lossy(tag).into_owned();
+            return Err(ResourceDataError::Syntax(tag));
+        }
+
+        Ok(Self::CertificationAuthority {
+            flags,
+            tag: String::from_utf8_lossy(tag).into_owned(),
+            value: buf[length as usize..].to_vec(),
+        })
+    }
+
+    fn parse_options(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let mut options = vec![];
+
+        while !buf.is_empty() {
+            let [c0, c1, l0, l1] = take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+            let length = u16::from_be_bytes([l0, l1]) as usize;
+            let data = buf.get(..length).ok_or(ResourceDataError::Truncated)?;
+            buf = &buf[length..];
+
+            options.push(EdnsOption {
+                code: u16::from_be_bytes([c0, c1]),
+                data: data.to_vec(),
+            });
+        }
+
+        Ok(Self::Opt(options))
     }
 
     fn parse_mail_info(value: &[u8]) -> Result<ResourceData, ResourceDataError> {
         let (mailbox, offset) = parse_label(value)?;
-        let error_mailbox = Label::try_from(&value[offset..])?;
+        let error_mailbox = parse_last_label(value, &value[offset..])?;
         Ok(Self::MailInfo {
             mailbox,
             error_mailbox,
@@ -427,7 +1531,7 @@ impl ResourceData {
         let mut text = vec![];
 
         while !buf.is_empty() {
-            let (s, offset) = parse_character_string(value)?;
+            let (s, offset) = parse_character_string(buf)?;
             buf = &buf[offset..];
             text.push(s);
         }
@@ -440,13 +1544,19 @@ impl ResourceData {
         let (name, offset) = parse_label(value)?;
         buf = &value[offset..];
         let (mail, offset) = parse_label(buf)?;
-        buf = &value[offset..];
-
-        let serial = buf.get_u32();
-        let refresh = buf.get_u32();
-        let retry = buf.get_u32();
-        let expire = buf.get_u32();
-        let minimum = buf.get_u32();
+        buf = &buf[offset..];
+
+        let mut next = || {
+            take(&mut buf)
+                .map(u32::from_be_bytes)
+                .ok_or(ResourceDataError::Truncated)
+        };
+        let serial = next()?;
+        let refresh = next()?;
+        let retry = next()?;
+        let expire = next()?;
+        let minimum = next()?;
+        expect_end(value, buf)?;
 
         Ok(Self::SOA {
             name,
@@ -464,16 +1574,42 @@ fn wrap_label(
     value: &[u8],
     data: fn(Label) -> ResourceData,
 ) -> Result<ResourceData, ResourceDataError> {
-    let label = Label::try_from(value)?;
+    let label = parse_last_label(value, value)?;
     Ok(data(label))
 }
 
+/// Parses the name that ends the data `value` from `buf`, the part of `value` left after the
+/// other fields.
+fn parse_last_label(value: &[u8], buf: &[u8]) -> Result<Label, ResourceDataError> {
+    let (label, offset) = parse_label(buf)?;
+    expect_end(value, &buf[offset..])?;
+    Ok(label)
+}
+
+/// Fails when octets of the data `value` are left in `buf` after its last field.
+fn expect_end(value: &[u8], buf: &[u8]) -> Result<(), ResourceDataError> {
+    match buf.len() {
+        0 => Ok(()),
+        left => Err(ResourceDataError::InvalidLength {
+            expected: value.len() - left,
+            found: value.len(),
+        }),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResourceRecordError {
     Label(LabelError),
     Data(ResourceDataError),
-    Type(UnregisteredType),
-    Class(UnregisteredClass),
+    /// The buffer ends before the type, class, TTL and RDLENGTH of the record
+    Truncated,
+    /// RDLENGTH is greater than the number of remaining bytes
+    DataOverrun {
+        length: u16,
+        remaining: usize,
+    },
+    /// The presentation format of the record doesn't give its TTL
+    MissingTimeToLive,
 }
 
 impl Display for ResourceRecordError {
@@ -482,8 +1618,12 @@ impl Display for ResourceRecordError {
         match self {
             Label(err) => err.fmt(f),
             Data(err) => err.fmt(f),
-            Type(err) => err.fmt(f),
-            Class(err) => err.fmt(f),
+            Truncated => "buffer doesn't contain a type, class, TTL and data length".fmt(f),
+            DataOverrun { length, remaining } => format!(
+                "resource data is of length '{length}', but only '{remaining}' bytes remain"
+            )
+            .fmt(f),
+            MissingTimeToLive => "record doesn't specify a TTL".fmt(f),
         }
     }
 }
@@ -502,62 +1642,36 @@ impl From<LabelError> for ResourceRecordError {
     }
 }
 
-impl From<UnregisteredType> for ResourceRecordError {
-    fn from(value: UnregisteredType) -> Self {
-        Self::Type(value)
-    }
-}
-
-impl From<UnregisteredClass> for ResourceRecordError {
-    fn from(value: UnregisteredClass) -> Self {
-        Self::Class(value)
-    }
-}
-
 pub fn parse_resource_record(value: &[u8]) -> Result<(ResourceRecord, usize), ResourceRecordError> {
-    use ResourceData::*;
+    use ResourceRecordError::{DataOverrun, Truncated};
+
     let (name, offset) = parse_label(value)?;
     let mut buf = &value[offset..];
     let mut record_offset = offset;
 
-    let typ: ResourceType = buf.get_u16().try_into()?;
-    record_offset += 2;
-    let class = buf.get_u16().try_into()?;
-    record_offset += 2;
-    let time_to_live = buf.get_u32();
-    record_offset += 4;
-
-    let length = buf.get_u16() as usize;
-    record_offset += 2;
-
-    assert!(length <= buf.remaining());
-
-    buf = &buf[..length];
-    record_offset += length;
-
-    let data = match typ {
-        ResourceType::A => ResourceData::parse_address(buf)?,
-        ResourceType::NS => wrap_label(buf, NameServer)?,
-        ResourceType::MD => wrap_label(buf, MailDevice)?,
-        ResourceType::MF => wrap_label(buf, MailForward)?,
-        ResourceType::CNAME => wrap_label(buf, CanonicalName)?,
-        ResourceType::SOA => ResourceData::parse_soa(buf)?,
-        ResourceType::MB => wrap_label(buf, MailBox)?,
-        ResourceType::MG => wrap_label(buf, MailGroup)?,
-        ResourceType::MR => wrap_label(buf, MailRename)?,
-        ResourceType::NULL => Null(buf.to_vec()),
-        ResourceType::WKS => todo!("implement wks parser"),
-        ResourceType::PTR => wrap_label(buf, Ptr)?,
-        ResourceType::HINFO => ResourceData::parse_host_info(buf)?,
-        ResourceType::MINFO => ResourceData::parse_mail_info(buf)?,
-        ResourceType::MX => ResourceData::parse_mail_exchange(buf)?,
-        ResourceType::TXT => ResourceData::parse_text(buf)?,
+    let (typ, class, time_to_live, length) = match take::<10>(&mut buf) {
+        Some([t0, t1, c0, c1, l0, l1, l2, l3, d0, d1]) => (
+            u16::from_be_bytes([t0, t1]),
+            u16::from_be_bytes([c0, c1]),
+            u32::from_be_bytes([l0, l1, l2, l3]),
+            u16::from_be_bytes([d0, d1]),
+        ),
+        None => return Err(Truncated),
     };
+    record_offset += 10;
+
+    buf = buf.get(..length as usize).ok_or(DataOverrun {
+        length,
+        remaining: buf.len(),
+    })?;
+    record_offset += length as usize;
+
+    let data = ResourceData::parse_in_class(typ.into(), class.into(), buf)?;
 
     Ok((
         ResourceRecord {
             name,
-            class,
+            class: class.into(),
             time_to_live,
             data,
         },
@@ -572,3 +1686,387 @@ impl TryFrom<&[u8]> for ResourceRecord {
         parse_resource_record(value).map(|t| t.0)
     }
 }
+
+#[cfg(test)]
+mod address {
+    use super::*;
+
+    #[test]
+    fn round_trip_ipv6_address() {
+        #[rustfmt::skip]
+        let buf = [
+            7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0,
+            0, 28, 0, 1, 0, 0, 0, 60, 0, 16,
+            0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01,
+        ];
+
+        let record = ResourceRecord::try_from(&buf[..]).unwrap();
+        assert_eq!(record.typ(), ResourceType::AAAA);
+        assert_eq!(
+            record.data,
+            ResourceData::Ipv6Address("2001:db8::1".parse().unwrap())
+        );
+        assert_eq!(record.data.to_string(), "2001:db8::1");
+        assert_eq!(Vec::try_from(record).unwrap(), buf);
+    }
+
+    #[test]
+    fn invalid_ipv6_address_length() {
+        assert_eq!(
+            ResourceData::parse(ResourceType::AAAA, &[0x20, 0x01, 0x0d, 0xb8]),
+            Err(ResourceDataError::InvalidLength {
+                expected: 16,
+                found: 4
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod service {
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn round_trip_service() {
+        #[rustfmt::skip]
+        let buf = [
+            5, b'_', b'l', b'd', b'a', b'p', 4, b'_', b't', b'c', b'p', 0,
+            0, 33, 0, 1, 0, 0, 0, 60, 0, 13,
+            0, 10, 0, 5, 0x01, 0x85, 2, b'd', b'c', 2, b'i', b'o', 0,
+        ];
+
+        let record = ResourceRecord::try_from(&buf[..]).unwrap();
+        assert_eq!(
+            record.data,
+            ResourceData::Service {
+                priority: 10,
+                weight: 5,
+                port: 389,
+                target: Label::parse_str("dc.io").unwrap(),
+            }
+        );
+        assert_eq!(record.data.to_string(), "10 5 389 dc.io.");
+        assert_eq!(
+            record.data.additional_name(),
+            Label::parse_str("dc.io").ok().as_ref()
+        );
+        assert_eq!(Vec::try_from(record).unwrap(), buf);
+    }
+
+    #[test]
+    fn target_is_never_compressed() {
+        let mut message = Message::new(1234);
+        message.answer(ResourceRecord {
+            name: Label::parse_str("_ldap._tcp.dc.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::Service {
+                priority: 10,
+                weight: 5,
+                port: 389,
+                target: Label::parse_str("dc.io").unwrap(),
+            },
+        });
+
+        let buf = Vec::try_from(message).unwrap();
+        assert_eq!(buf[buf.len() - 7..], [2, b'd', b'c', 2, b'i', b'o', 0]);
+    }
+}
+
+#[cfg(test)]
+mod well_known_services {
+    use super::*;
+
+    #[test]
+    fn round_trip_bit_map() {
+        #[rustfmt::skip]
+        let buf = [
+            7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0,
+            0, 11, 0, 1, 0, 0, 0, 60, 0, 9,
+            10, 0, 0, 1, 6, 0b0000_0000, 0b0000_0110, 0, 0b0100_0000,
+        ];
+
+        let record = ResourceRecord::try_from(&buf[..]).unwrap();
+        let ResourceData::WKS {
+            address,
+            protocol,
+            bit_map,
+        } = &record.data
+        else {
+            panic!("expected a WKS record, but found {:?}", record.data);
+        };
+        assert_eq!(*address, Ipv4Addr::new(10, 0, 0, 1));
+        assert_eq!(*protocol, 6);
+        assert_eq!(bit_map.ports().collect::<Vec<_>>(), [13, 14, 25]);
+        assert_eq!(record.data.to_string(), "10.0.0.1 TCP 13 14 smtp");
+        assert_eq!(Vec::try_from(record).unwrap(), buf);
+    }
+
+    #[test]
+    fn missing_protocol() {
+        assert_eq!(
+            ResourceData::parse(ResourceType::WKS, &[10, 0, 0, 1]),
+            Err(ResourceDataError::Truncated)
+        );
+    }
+}
+
+#[cfg(test)]
+mod unknown {
+    use super::*;
+
+    #[test]
+    fn round_trip_unknown_data() {
+        #[rustfmt::skip]
+        let buf = [
+            7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0,
+            0x02, 0xdb, 0, 32, 0, 0, 0, 60, 0, 4,
+            0x0a, 0x00, 0x00, 0x01,
+        ];
+
+        let record = ResourceRecord::try_from(&buf[..]).unwrap();
+        assert_eq!(record.typ(), ResourceType::Unknown(731));
+        assert_eq!(record.class, ResourceClass::Unknown(32));
+        assert_eq!(
+            record.data,
+            ResourceData::Unknown {
+                typ: 731,
+                data: vec![0x0a, 0x00, 0x00, 0x01]
+            }
+        );
+        assert_eq!(Vec::try_from(record).unwrap(), buf);
+    }
+
+    #[test]
+    fn generic_presentation() {
+        let data = ResourceData::Unknown {
+            typ: 731,
+            data: vec![0x0a, 0x00, 0x00, 0x01],
+        };
+        assert_eq!(data.to_string(), "\\# 4 0a000001");
+        assert_eq!(
+            ResourceData::from_generic(ResourceType::Unknown(731), "\\# 4 0a00 0001"),
+            Ok(data)
+        );
+
+        let empty = ResourceData::Unknown {
+            typ: 62347,
+            data: vec![],
+        };
+        assert_eq!(empty.to_string(), "\\# 0");
+        assert_eq!(
+            ResourceData::from_generic(ResourceType::Unknown(62347), "\\# 0"),
+            Ok(empty)
+        );
+    }
+
+    #[test]
+    fn generic_presentation_of_known_types() {
+        assert_eq!(
+            ResourceData::from_generic(ResourceType::A, "\\# 4 0A000001"),
+            Ok(ResourceData::Address(Ipv4Addr::new(10, 0, 0, 1)))
+        );
+        assert_eq!(
+            ResourceData::from_generic(ResourceType::A, "\\# 3 0a0000"),
+            Err(ResourceDataError::InvalidLength {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert_eq!(
+            ResourceData::from_generic(ResourceType::A, "\\# 4 0a00"),
+            Err(ResourceDataError::InvalidLength {
+                expected: 4,
+                found: 2
+            })
+        );
+        assert!(ResourceData::from_generic(ResourceType::A, "10.0.0.1").is_err());
+    }
+}
+
+#[cfg(test)]
+mod security {
+    use super::*;
+
+    fn round_trip(data: ResourceData, buf: &[u8], text: &str) {
+        assert_eq!(ResourceData::parse(data.typ(), buf), Ok(data.clone()));
+        assert_eq!(data.to_string(), text);
+        assert_eq!(Vec::try_from(data).unwrap(), buf);
+    }
+
+    #[test]
+    fn round_trip_certification_authority() {
+        #[rustfmt::skip]
+        let buf = [
+            128, 5, b'i', b's', b's', b'u', b'e',
+            b'c', b'a', b'.', b'n', b'e', b't', b';', b' ', b'a', b'=', b'"', b'b', b'"',
+        ];
+
+        round_trip(
+            ResourceData::CertificationAuthority {
+                flags: 128,
+                tag: "issue".to_owned(),
+                value: b"ca.net; a=\"b\"".to_vec(),
+            },
+            &buf,
+            r#"128 issue "ca.net; a=\"b\"""#,
+        );
+    }
+
+    #[test]
+    fn invalid_certification_authority_tag() {
+        assert_eq!(
+            ResourceData::parse(ResourceType::CAA, &[0, 0]),
+            Err(ResourceDataError::Syntax("".to_owned()))
+        );
+        assert_eq!(
+            ResourceData::parse(ResourceType::CAA, &[0, 2, b'a', b'-']),
+            Err(ResourceDataError::Syntax("a-".to_owned()))
+        );
+        assert_eq!(
+            ResourceData::parse(ResourceType::CAA, &[0, 5, b'i']),
+            Err(ResourceDataError::Truncated)
+        );
+
+        let tag = "a".repeat(256);
+        assert_eq!(
+            format!("CAA 0 {tag} \"ca.net\"").parse::<ResourceData>(),
+            Err(ResourceDataError::Syntax(tag.clone()))
+        );
+        let data = ResourceData::CertificationAuthority {
+            flags: 0,
+            tag,
+            value: b"ca.net".to_vec(),
+        };
+        assert_eq!(
+            Vec::try_from(data),
+            Err(ResourceDataError::FieldTooLong {
+                max: 255,
+                found: 256
+            })
+        );
+    }
+
+    #[test]
+    fn round_trip_tls_association() {
+        round_trip(
+            ResourceData::TlsAssociation {
+                usage: 3,
+                selector: 1,
+                matching_type: 1,
+                data: vec![0x0d, 0x6f, 0xce, 0x33],
+            },
+            &[3, 1, 1, 0x0d, 0x6f, 0xce, 0x33],
+            "3 1 1 0d6fce33",
+        );
+    }
+
+    #[test]
+    fn round_trip_ssh_fingerprint() {
+        round_trip(
+            ResourceData::SshFingerprint {
+                algorithm: 4,
+                fingerprint_type: 2,
+                fingerprint: vec![0x12, 0x3a, 0xbc],
+            },
+            &[4, 2, 0x12, 0x3a, 0xbc],
+            "4 2 123abc",
+        );
+        assert_eq!(
+            ResourceData::parse(ResourceType::SSHFP, &[4]),
+            Err(ResourceDataError::Truncated)
+        );
+    }
+}
+
+#[cfg(test)]
+mod naming_authority_pointer {
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn round_trip_naming_authority_pointer() {
+        #[rustfmt::skip]
+        let buf = [
+            0, 100, 0, 10,
+            1, b'S',
+            7, b'S', b'I', b'P', b'+', b'D', b'2', b'U',
+            0,
+            4, b'_', b's', b'i', b'p', 4, b'_', b'u', b'd', b'p', 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0,
+        ];
+
+        let data = ResourceData::parse(ResourceType::NAPTR, &buf).unwrap();
+        assert_eq!(
+            data,
+            ResourceData::NamingAuthorityPointer {
+                order: 100,
+                preference: 10,
+                flags: CharacterString::String(b"S".to_vec()),
+                services: CharacterString::String(b"SIP+D2U".to_vec()),
+                regexp: CharacterString::String(vec![]),
+                replacement: Label::parse_str("_sip._udp.example").unwrap(),
+            }
+        );
+        assert_eq!(
+            data.to_string(),
+            r#"100 10 "S" "SIP+D2U" "" _sip._udp.example."#
+        );
+        assert_eq!(Vec::try_from(data).unwrap(), buf);
+    }
+
+    #[test]
+    fn replacement_is_never_compressed() {
+        let mut message = Message::new(0);
+        message.answer(ResourceRecord {
+            name: Label::parse_str("example").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::NamingAuthorityPointer {
+                order: 1,
+                preference: 1,
+                flags: CharacterString::String(vec![]),
+                services: CharacterString::String(vec![]),
+                regexp: CharacterString::String(vec![]),
+                replacement: Label::parse_str("example").unwrap(),
+            },
+        });
+
+        let buf = Vec::try_from(message).unwrap();
+        assert!(buf.ends_with(&[7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0]));
+    }
+}
+
+#[cfg(test)]
+mod trailing_octets {
+    use super::*;
+
+    #[test]
+    fn reject_octets_after_the_last_field() {
+        #[rustfmt::skip]
+        let cases: [(ResourceType, &[u8]); 7] = [
+            (ResourceType::NS, &[2, b'n', b's', 0]),
+            (ResourceType::SOA, &[0, 0, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5]),
+            (ResourceType::MX, &[0, 10, 0]),
+            (ResourceType::HINFO, &[1, b'a', 1, b'b']),
+            (ResourceType::SRV, &[0, 1, 0, 2, 0, 3, 0]),
+            (ResourceType::NAPTR, &[0, 1, 0, 2, 0, 0, 0, 0]),
+            (ResourceType::MINFO, &[0, 0]),
+        ];
+
+        for (typ, buf) in cases {
+            assert!(ResourceData::parse(typ, buf).is_ok(), "{typ}");
+
+            let mut padded = buf.to_vec();
+            padded.push(0);
+            assert_eq!(
+                ResourceData::parse(typ, &padded),
+                Err(ResourceDataError::InvalidLength {
+                    expected: buf.len(),
+                    found: buf.len() + 1,
+                }),
+                "{typ}"
+            );
+        }
+    }
+}
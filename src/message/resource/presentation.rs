@@ -0,0 +1,540 @@
+//! The presentation format of resource records used by master files ([RFC1035 5.1]), where a
+//! record is written on a single line as its owner, TTL, class, type and data:
+//!
+//! ```txt
+//! example.com. 300 IN MX 10 mail.example.com.
+//! ```
+//!
+//! [RFC1035 5.1]: <https://datatracker.ietf.org/doc/html/rfc1035#section-5.1>
+use std::{
+    fmt::{self, Display},
+    slice,
+    str::FromStr,
+};
+
+use super::{
+    parse_timestamp, protocol_number, service_port, ResourceData, ResourceDataError,
+    ResourceRecord, ResourceRecordError, SvcParams, TypeBitmap,
+};
+use crate::message::{
+    encoding::{decode_base32hex, decode_base64, decode_escaped, decode_hex},
+    CharacterString, Label, ResourceClass, ResourceType,
+};
+
+/// Splits `text` around blanks, keeping quoted strings and escaped characters within a single
+/// token. Returns [`None`] when a quote or an escape is left open.
+pub fn split_tokens(text: &str) -> Option<Vec<&str>> {
+    let mut tokens = vec![];
+    let mut start = None;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (index, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => quoted = !quoted,
+            _ if c.is_whitespace() && !quoted => {
+                if let Some(start) = start.take() {
+                    tokens.push(&text[start..index]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(index);
+    }
+
+    if let Some(start) = start {
+        tokens.push(&text[start..]);
+    }
+
+    match quoted || escaped {
+        true => None,
+        false => Some(tokens),
+    }
+}
+
+/// Reads a token which may be enclosed in double quotes.
+fn decode_string(token: &str) -> Result<Vec<u8>, ResourceDataError> {
+    let syntax = || ResourceDataError::Syntax(token.to_owned());
+    let value = match token.strip_prefix('"') {
+        Some(quoted) => quoted.strip_suffix('"').ok_or_else(syntax)?,
+        None => token,
+    };
+    decode_escaped(value).ok_or_else(syntax)
+}
+
+/// Reads a `<character-string>` token, which is at most 255 octets long.
+fn parse_character_string(token: &str) -> Result<CharacterString, ResourceDataError> {
+    match decode_string(token)? {
+        bytes if bytes.len() <= u8::MAX as usize => Ok(CharacterString::String(bytes)),
+        _ => Err(ResourceDataError::Syntax(token.to_owned())),
+    }
+}
+
+/// The tokens of the data of a single record, read one field at a time.
+struct Fields<'a, 'b> {
+    tokens: slice::Iter<'b, &'a str>,
+    origin: Option<&'b Label>,
+}
+
+impl<'a> Fields<'a, '_> {
+    fn token(&mut self) -> Result<&'a str, ResourceDataError> {
+        self.tokens
+            .next()
+            .copied()
+            .ok_or(ResourceDataError::Truncated)
+    }
+
+    fn parse<T: FromStr>(&mut self) -> Result<T, ResourceDataError> {
+        let token = self.token()?;
+        token
+            .parse()
+            .map_err(|_| ResourceDataError::Syntax(token.to_owned()))
+    }
+
+    fn name(&mut self) -> Result<Label, ResourceDataError> {
+        let token = self.token()?;
+        Ok(match self.origin {
+            Some(origin) => Label::parse_relative(token, origin)?,
+            None => token.parse()?,
+        })
+    }
+
+    fn string(&mut self) -> Result<CharacterString, ResourceDataError> {
+        parse_character_string(self.token()?)
+    }
+
+    fn timestamp(&mut self) -> Result<u32, ResourceDataError> {
+        let token = self.token()?;
+        parse_timestamp(token).ok_or_else(|| ResourceDataError::Syntax(token.to_owned()))
+    }
+
+    /// Reads the salt of NSEC3 and NSEC3PARAM, which is at most 255 octets.
+    fn salt(&mut self) -> Result<Vec<u8>, ResourceDataError> {
+        match self.token()? {
+            "-" => Ok(vec![]),
+            token => decode_hex(token)
+                .filter(|salt| salt.len() <= u8::MAX as usize)
+                .ok_or_else(|| ResourceDataError::Syntax(token.to_owned())),
+        }
+    }
+
+    /// Decodes the remaining tokens, of which there must be at least one, as a single value.
+    fn rest(&mut self, decode: fn(&str) -> Option<Vec<u8>>) -> Result<Vec<u8>, ResourceDataError> {
+        let text = self.tokens.as_slice().concat();
+        self.tokens.by_ref().for_each(drop);
+
+        match text.is_empty() {
+            true => Err(ResourceDataError::Truncated),
+            false => decode(&text).ok_or(ResourceDataError::Syntax(text)),
+        }
+    }
+
+    fn types(&mut self) -> Result<TypeBitmap, ResourceDataError> {
+        self.tokens
+            .by_ref()
+            .map(|token| {
+                token
+                    .parse::<ResourceType>()
+                    .map_err(|_| ResourceDataError::Syntax(token.to_string()))
+            })
+            .collect()
+    }
+
+    fn finish(mut self) -> Result<(), ResourceDataError> {
+        match self.tokens.next() {
+            Some(token) => Err(ResourceDataError::Syntax(token.to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ResourceData {
+    /// Parses the presentation format of the data of a record of type `typ`, already split into
+    /// [`tokens`][split_tokens].
+    ///
+    /// Names are relative to `origin` when it's given, see [`Label::parse_relative`], and absolute
+    /// otherwise. The generic `\# <length> <hex>` format is accepted for every type, and is the
+    /// only one of the NULL, OPT and unknown types.
+    pub fn from_tokens(
+        typ: ResourceType,
+        tokens: &[&str],
+        origin: Option<&Label>,
+    ) -> Result<ResourceData, ResourceDataError> {
+        use ResourceData::*;
+
+        if tokens.first() == Some(&"\\#") {
+            return ResourceData::from_generic(typ, &tokens.join(" "));
+        }
+
+        let mut fields = Fields {
+            tokens: tokens.iter(),
+            origin,
+        };
+
+        let data = match typ {
+            ResourceType::A => Address(fields.parse()?),
+            ResourceType::NS => NameServer(fields.name()?),
+            ResourceType::MD => MailDevice(fields.name()?),
+            ResourceType::MF => MailForward(fields.name()?),
+            ResourceType::CNAME => CanonicalName(fields.name()?),
+            ResourceType::SOA => SOA {
+                name: fields.name()?,
+                mail: fields.name()?,
+                serial: fields.parse()?,
+                refresh: fields.parse()?,
+                retry: fields.parse()?,
+                expire: fields.parse()?,
+                minimum: fields.parse()?,
+            },
+            ResourceType::MB => MailBox(fields.name()?),
+            ResourceType::MG => MailGroup(fields.name()?),
+            ResourceType::MR => MailRename(fields.name()?),
+            ResourceType::WKS => {
+                let address = fields.parse()?;
+                let protocol = fields.token()?;
+                let protocol = protocol_number(protocol)
+                    .or_else(|| protocol.parse().ok())
+                    .ok_or_else(|| ResourceDataError::Syntax(protocol.to_owned()))?;
+                let bit_map = fields
+                    .tokens
+                    .by_ref()
+                    .map(|service| {
+                        service_port(service)
+                            .or_else(|| service.parse().ok())
+                            .ok_or_else(|| ResourceDataError::Syntax(service.to_string()))
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                WKS {
+                    address,
+                    protocol,
+                    bit_map,
+                }
+            }
+            ResourceType::PTR => Ptr(fields.name()?),
+            ResourceType::HINFO => HostInfo {
+                cpu: fields.string()?,
+                os: fields.string()?,
+            },
+            ResourceType::MINFO => MailInfo {
+                mailbox: fields.name()?,
+                error_mailbox: fields.name()?,
+            },
+            ResourceType::MX => MailExchange {
+                preference: fields.parse()?,
+                exchange: fields.name()?,
+            },
+            ResourceType::TXT => {
+                let text = fields
+                    .tokens
+                    .by_ref()
+                    .map(|token| parse_character_string(token))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                match text.is_empty() {
+                    true => return Err(ResourceDataError::Truncated),
+                    false => Text(text),
+                }
+            }
+            ResourceType::AAAA => Ipv6Address(fields.parse()?),
+            ResourceType::SRV => Service {
+                priority: fields.parse()?,
+                weight: fields.parse()?,
+                port: fields.parse()?,
+                target: fields.name()?,
+            },
+            ResourceType::NAPTR => NamingAuthorityPointer {
+                order: fields.parse()?,
+                preference: fields.parse()?,
+                flags: fields.string()?,
+                services: fields.string()?,
+                regexp: fields.string()?,
+                replacement: fields.name()?,
+            },
+            ResourceType::DS => DelegationSigner {
+                key_tag: fields.parse()?,
+                algorithm: fields.parse()?,
+                digest_type: fields.parse()?,
+                digest: fields.rest(decode_hex)?,
+            },
+            ResourceType::SSHFP => SshFingerprint {
+                algorithm: fields.parse()?,
+                fingerprint_type: fields.parse()?,
+                fingerprint: fields.rest(decode_hex)?,
+            },
+            ResourceType::RRSIG => Signature {
+                type_covered: fields.parse()?,
+                algorithm: fields.parse()?,
+                labels: fields.parse()?,
+                original_time_to_live: fields.parse()?,
+                expiration: fields.timestamp()?,
+                inception: fields.timestamp()?,
+                key_tag: fields.parse()?,
+                signer: fields.name()?,
+                signature: fields.rest(decode_base64)?,
+            },
+            ResourceType::NSEC => NextSecure {
+                next: fields.name()?,
+                types: fields.types()?,
+            },
+            ResourceType::DNSKEY => DnsKey {
+                flags: fields.parse()?,
+                protocol: fields.parse()?,
+                algorithm: fields.parse()?,
+                public_key: fields.rest(decode_base64)?,
+            },
+            ResourceType::NSEC3 => HashedNextSecure {
+                hash_algorithm: fields.parse()?,
+                flags: fields.parse()?,
+                iterations: fields.parse()?,
+                salt: fields.salt()?,
+                next_hashed: {
+                    let token = fields.token()?;
+                    decode_base32hex(token)
+                        .filter(|hash| hash.len() <= u8::MAX as usize)
+                        .ok_or_else(|| ResourceDataError::Syntax(token.to_owned()))?
+                },
+                types: fields.types()?,
+            },
+            ResourceType::NSEC3PARAM => HashedNextSecureParameters {
+                hash_algorithm: fields.parse()?,
+                flags: fields.parse()?,
+                iterations: fields.parse()?,
+                salt: fields.salt()?,
+            },
+            ResourceType::TLSA => TlsAssociation {
+                usage: fields.parse()?,
+                selector: fields.parse()?,
+                matching_type: fields.parse()?,
+                data: fields.rest(decode_hex)?,
+            },
+            ResourceType::SVCB | ResourceType::HTTPS => {
+                let priority = fields.parse()?;
+                let target = fields.name()?;
+                let params = fields.tokens.as_slice().join(" ").parse::<SvcParams>()?;
+                fields.tokens.by_ref().for_each(drop);
+
+                match typ {
+                    ResourceType::SVCB => ServiceBinding {
+                        priority,
+                        target,
+                        params,
+                    },
+                    _ => HttpsServiceBinding {
+                        priority,
+                        target,
+                        params,
+                    },
+                }
+            }
+            ResourceType::CAA => {
+                let flags = fields.parse()?;
+                let tag = fields.token()?;
+                if tag.is_empty()
+                    || tag.len() > u8::MAX as usize
+                    || !tag.bytes().all(|c| c.is_ascii_alphanumeric())
+                {
+                    return Err(ResourceDataError::Syntax(tag.to_owned()));
+                }
+
+                CertificationAuthority {
+                    flags,
+                    tag: tag.to_owned(),
+                    value: decode_string(fields.token()?)?,
+                }
+            }
+            ResourceType::NULL
+            | ResourceType::OPT
+            | ResourceType::ANY
+            | ResourceType::Unknown(_) => {
+                return Err(ResourceDataError::Syntax(tokens.join(" ")));
+            }
+        };
+
+        fields.finish()?;
+        Ok(data)
+    }
+}
+
+impl FromStr for ResourceData {
+    type Err = ResourceDataError;
+
+    /// Parses the type of the data followed by its presentation format, e.g. `MX 10 mail.`, as
+    /// the type can't be told from the data alone.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = split_tokens(s).ok_or_else(|| ResourceDataError::Syntax(s.to_owned()))?;
+        let (typ, tokens) = tokens.split_first().ok_or(ResourceDataError::Truncated)?;
+        let typ = typ
+            .parse()
+            .map_err(|_| ResourceDataError::Syntax(typ.to_string()))?;
+        ResourceData::from_tokens(typ, tokens, None)
+    }
+}
+
+impl Display for ResourceRecord {
+    /// Presents the record on a single line of a master file, with an absolute owner name.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.name,
+            self.time_to_live,
+            self.class,
+            self.typ(),
+            self.data
+        )
+    }
+}
+
+impl FromStr for ResourceRecord {
+    type Err = ResourceRecordError;
+
+    /// Parses a record on a single line of a master file, i.e.
+    /// `<owner> [<TTL>] [<class>] <type> <RDATA>` where the TTL and class may be given in either
+    /// order. The class defaults to IN, but the TTL must be present as there is no previous record
+    /// to inherit it from.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let syntax = |token: &str| ResourceDataError::Syntax(token.to_owned());
+
+        let tokens = split_tokens(s).ok_or_else(|| syntax(s))?;
+        let (name, mut tokens) = tokens.split_first().ok_or(ResourceDataError::Truncated)?;
+        let name = name.parse::<Label>()?;
+
+        let mut time_to_live = None;
+        let mut class = None;
+        while let Some((token, rest)) = tokens.split_first() {
+            match token.parse() {
+                Ok(ttl) if time_to_live.is_none() => time_to_live = Some(ttl),
+                _ => match token.parse::<ResourceClass>() {
+                    Ok(value) if class.is_none() => class = Some(value),
+                    _ => break,
+                },
+            }
+            tokens = rest;
+        }
+
+        let (typ, tokens) = tokens.split_first().ok_or(ResourceDataError::Truncated)?;
+        let typ = typ.parse::<ResourceType>().map_err(|_| syntax(typ))?;
+
+        Ok(ResourceRecord {
+            name,
+            class: class.unwrap_or(ResourceClass::IN),
+            time_to_live: time_to_live.ok_or(ResourceRecordError::MissingTimeToLive)?,
+            data: ResourceData::from_tokens(typ, tokens, None)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod master_file {
+    use super::*;
+
+    fn round_trip(text: &str) {
+        let record = text.parse::<ResourceRecord>().unwrap();
+        assert_eq!(record.to_string(), text);
+    }
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            split_tokens("  a \"b c\" d\\ e\t\"\\\"\" "),
+            Some(vec!["a", "\"b c\"", "d\\ e", "\"\\\"\""])
+        );
+        assert_eq!(split_tokens("\"open"), None);
+        assert_eq!(split_tokens("a\\"), None);
+    }
+
+    #[test]
+    fn round_trip_records() {
+        round_trip("example.com. 300 IN MX 10 mail.example.com.");
+        round_trip("example.com. 300 IN A 192.0.2.1");
+        round_trip("example.com. 300 IN AAAA 2001:db8::1");
+        round_trip("example.com. 300 IN TXT \"v=spf1 -all\" \"a\\\"b\\009\"");
+        round_trip("example.com. 300 IN HINFO \"VAX-11/780\" \"UNIX\"");
+        round_trip(
+            "example.com. 3600 IN SOA ns.example.com. admin.example.com. 1 7200 900 1209600 300",
+        );
+        round_trip("example.com. 60 CH WKS 10.0.0.1 TCP 13 smtp http");
+        round_trip("_sip._udp.example.com. 60 IN SRV 10 5 5060 sip.example.com.");
+        round_trip("example.com. 60 IN NAPTR 100 10 \"S\" \"SIP+D2U\" \"\" _sip._udp.example.com.");
+        round_trip("example.com. 60 IN CAA 0 issue \"ca.net\"");
+        round_trip("example.com. 60 IN DS 60485 5 1 2bb183af5f22588179a53b0a98631fad1a292118");
+        round_trip("example.com. 60 IN NSEC host.example.com. A MX RRSIG NSEC");
+        round_trip("example.com. 60 IN NSEC3PARAM 1 0 12 aabbccdd");
+        round_trip("example.com. 60 IN HTTPS 1 . alpn=h2,h3 port=443");
+        round_trip("example.com. 60 IN TYPE731 \\# 4 0a000001");
+        round_trip("a\\.b.example.com. 0 NONE ANY \\# 0");
+    }
+
+    #[test]
+    fn optional_fields() {
+        let record = "Example.com. IN 300 mx 10 mail".parse::<ResourceRecord>();
+        assert_eq!(
+            record,
+            "example.com. 300 MX 10 mail.".parse::<ResourceRecord>()
+        );
+        assert_eq!(record.unwrap().class, ResourceClass::IN);
+
+        assert_eq!(
+            "example.com. IN A 10.0.0.1".parse::<ResourceRecord>(),
+            Err(ResourceRecordError::MissingTimeToLive)
+        );
+    }
+
+    #[test]
+    fn multiple_tokens_values() {
+        let data = "DNSKEY 256 3 5 AQPSKmynfzW4kyBv015MUG2DeIQ3 Cbl+BBZH4b/0PY1kxkmvHjcZc8no kfzj31GajIQKY+5CptLr3buXA10h WqTkF7H6RfoRqXQeogmMHfpftf6z Mv1LyBUgia7za6ZEzOJBOztyvhjL 742iU/TpPSEDhm2SNKLijfUppn1U aNvv4w==".parse::<ResourceData>().unwrap();
+        let ResourceData::DnsKey { public_key, .. } = data else {
+            panic!("expected a DNSKEY record, but found {data:?}");
+        };
+        assert_eq!(public_key.len(), 130);
+    }
+
+    #[test]
+    fn invalid_data() {
+        use ResourceDataError::*;
+
+        assert_eq!("MX 10".parse::<ResourceData>(), Err(Truncated));
+        assert_eq!(
+            "MX 10 mail. extra".parse::<ResourceData>(),
+            Err(Syntax("extra".to_owned()))
+        );
+        assert_eq!(
+            "A 10.0.0".parse::<ResourceData>(),
+            Err(Syntax("10.0.0".to_owned()))
+        );
+        assert_eq!(
+            "NULL 00".parse::<ResourceData>(),
+            Err(Syntax("00".to_owned()))
+        );
+        assert_eq!(
+            "NULL \\# 1 00".parse::<ResourceData>(),
+            Ok(ResourceData::Null(vec![0]))
+        );
+        assert_eq!("TXT".parse::<ResourceData>(), Err(Truncated));
+        assert_eq!(
+            format!("TXT {}", "a".repeat(256)).parse::<ResourceData>(),
+            Err(Syntax("a".repeat(256)))
+        );
+    }
+
+    #[test]
+    fn relative_names() {
+        let origin = "example.com".parse().unwrap();
+        let data = ResourceData::from_tokens(
+            ResourceType::SOA,
+            &["@", "admin", "1", "2", "3", "4", "5"],
+            Some(&origin),
+        );
+        assert_eq!(
+            data,
+            "SOA example.com. admin.example.com. 1 2 3 4 5".parse()
+        );
+
+        let data = ResourceData::from_tokens(ResourceType::CNAME, &["www.other."], Some(&origin));
+        assert_eq!(data, "CNAME www.other".parse());
+    }
+}
@@ -0,0 +1,764 @@
+//! Service binding records ([RFC9460]) tell a client where a service is hosted and how to reach
+//! it, through a list of `key=value` service parameters following the target name:
+//!
+//! ```txt
+//!     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+//!     |                  SvcPriority                  |
+//!     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+//!     /                  TargetName                   /
+//!     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+//!     /                  SvcParams                    /
+//!     +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+//! ```
+//!
+//! where every parameter is encoded as its key, the length of its value and the value itself, in
+//! strictly increasing key order.
+//!
+//! [RFC9460]: <https://datatracker.ietf.org/doc/html/rfc9460>
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use super::{take, ResourceData, ResourceDataError};
+use crate::message::{
+    encoding::{decode_base64, decode_escaped, write_base64},
+    parse_label, Counter, Sink,
+};
+
+/// The registered keys of the service parameters, see [RFC9460 14.3.2], where key 65535 is
+/// reserved as an invalid key.
+///
+/// [RFC9460 14.3.2]: <https://datatracker.ietf.org/doc/html/rfc9460#section-14.3.2>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvcParamKey {
+    /// The keys a client must understand to use the record
+    Mandatory,
+
+    /// The application protocols supported by the endpoint
+    Alpn,
+
+    /// The default protocol of the scheme isn't supported
+    NoDefaultAlpn,
+
+    /// The port the service listens on
+    Port,
+
+    /// IPv4 addresses that may be used to reach the service
+    Ipv4Hint,
+
+    /// An encrypted client hello configuration list
+    Ech,
+
+    /// IPv6 addresses that may be used to reach the service
+    Ipv6Hint,
+
+    /// A key without a registered name
+    Unknown(u16),
+}
+
+impl From<u16> for SvcParamKey {
+    fn from(value: u16) -> Self {
+        use SvcParamKey::*;
+        match value {
+            0 => Mandatory,
+            1 => Alpn,
+            2 => NoDefaultAlpn,
+            3 => Port,
+            4 => Ipv4Hint,
+            5 => Ech,
+            6 => Ipv6Hint,
+            key => Unknown(key),
+        }
+    }
+}
+
+impl From<SvcParamKey> for u16 {
+    fn from(value: SvcParamKey) -> Self {
+        use SvcParamKey::*;
+        match value {
+            Mandatory => 0,
+            Alpn => 1,
+            NoDefaultAlpn => 2,
+            Port => 3,
+            Ipv4Hint => 4,
+            Ech => 5,
+            Ipv6Hint => 6,
+            Unknown(key) => key,
+        }
+    }
+}
+
+impl Display for SvcParamKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use SvcParamKey::*;
+        match self {
+            Mandatory => "mandatory".fmt(f),
+            Alpn => "alpn".fmt(f),
+            NoDefaultAlpn => "no-default-alpn".fmt(f),
+            Port => "port".fmt(f),
+            Ipv4Hint => "ipv4hint".fmt(f),
+            Ech => "ech".fmt(f),
+            Ipv6Hint => "ipv6hint".fmt(f),
+            Unknown(key) => write!(f, "key{key}"),
+        }
+    }
+}
+
+impl FromStr for SvcParamKey {
+    type Err = ResourceDataError;
+
+    /// Parses the name of a key, or the generic `key<number>` form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use SvcParamKey::*;
+        Ok(match s {
+            "mandatory" => Mandatory,
+            "alpn" => Alpn,
+            "no-default-alpn" => NoDefaultAlpn,
+            "port" => Port,
+            "ipv4hint" => Ipv4Hint,
+            "ech" => Ech,
+            "ipv6hint" => Ipv6Hint,
+            _ => match s.strip_prefix("key") {
+                Some(key) if key.bytes().all(|c| c.is_ascii_digit()) => key
+                    .parse::<u16>()
+                    .map_err(|_| ResourceDataError::Syntax(s.to_owned()))?
+                    .into(),
+                _ => return Err(ResourceDataError::Syntax(s.to_owned())),
+            },
+        })
+    }
+}
+
+/// A single service parameter along with its typed value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SvcParam {
+    Mandatory(Vec<SvcParamKey>),
+    /// The protocol identifiers, e.g. `h2` or `h3`
+    Alpn(Vec<Vec<u8>>),
+    NoDefaultAlpn,
+    Port(u16),
+    Ipv4Hint(Vec<Ipv4Addr>),
+    /// The `ECHConfigList` of [RFC9849], kept in its binary form
+    ///
+    /// [RFC9849]: <https://datatracker.ietf.org/doc/html/rfc9849>
+    Ech(Vec<u8>),
+    Ipv6Hint(Vec<Ipv6Addr>),
+    Unknown {
+        key: u16,
+        value: Vec<u8>,
+    },
+}
+
+impl SvcParam {
+    pub fn key(&self) -> SvcParamKey {
+        use SvcParamKey::*;
+        match self {
+            SvcParam::Mandatory(_) => Mandatory,
+            SvcParam::Alpn(_) => Alpn,
+            SvcParam::NoDefaultAlpn => NoDefaultAlpn,
+            SvcParam::Port(_) => Port,
+            SvcParam::Ipv4Hint(_) => Ipv4Hint,
+            SvcParam::Ech(_) => Ech,
+            SvcParam::Ipv6Hint(_) => Ipv6Hint,
+            SvcParam::Unknown { key, .. } => (*key).into(),
+        }
+    }
+
+    /// Parses the wire format of the value of the parameter `key`.
+    pub fn parse(key: SvcParamKey, value: &[u8]) -> Result<Self, ResourceDataError> {
+        let invalid = ResourceDataError::InvalidServiceParameter(key);
+
+        Ok(match key {
+            SvcParamKey::Mandatory if !value.is_empty() && value.len() % 2 == 0 => {
+                let keys = value
+                    .chunks(2)
+                    .map(|key| u16::from_be_bytes([key[0], key[1]]))
+                    .collect::<Vec<_>>();
+
+                // Keys must be listed once in increasing order, and never list `mandatory` itself
+                if keys[0] == 0 || keys.windows(2).any(|pair| pair[0] >= pair[1]) {
+                    return Err(invalid);
+                }
+                SvcParam::Mandatory(keys.into_iter().map(SvcParamKey::from).collect())
+            }
+            SvcParamKey::Alpn if !value.is_empty() => {
+                let mut buf = value;
+                let mut ids = vec![];
+                while let Some([length]) = take(&mut buf) {
+                    match buf.get(..length as usize) {
+                        Some(id) if length > 0 => ids.push(id.to_vec()),
+                        _ => return Err(invalid),
+                    }
+                    buf = &buf[length as usize..];
+                }
+                SvcParam::Alpn(ids)
+            }
+            SvcParamKey::NoDefaultAlpn if value.is_empty() => SvcParam::NoDefaultAlpn,
+            SvcParamKey::Port => {
+                SvcParam::Port(u16::from_be_bytes(value.try_into().map_err(|_| invalid)?))
+            }
+            SvcParamKey::Ipv4Hint if !value.is_empty() && value.len() % 4 == 0 => {
+                SvcParam::Ipv4Hint(
+                    value
+                        .chunks(4)
+                        .map(|ip| Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]))
+                        .collect(),
+                )
+            }
+            SvcParamKey::Ech => SvcParam::Ech(value.to_vec()),
+            SvcParamKey::Ipv6Hint if !value.is_empty() && value.len() % 16 == 0 => {
+                SvcParam::Ipv6Hint(
+                    value
+                        .chunks(16)
+                        .map(|ip| u128::from_be_bytes(ip.try_into().unwrap()).into())
+                        .collect(),
+                )
+            }
+            SvcParamKey::Unknown(key) => SvcParam::Unknown {
+                key,
+                value: value.to_vec(),
+            },
+            _ => return Err(invalid),
+        })
+    }
+
+    /// Appends the wire format of the value, without its key and length, to `buf`.
+    ///
+    /// Fails when an ALPN id is longer than its length octet can count.
+    pub fn write_value(&self, buf: &mut impl Sink) -> Result<(), ResourceDataError> {
+        match self {
+            SvcParam::Mandatory(keys) => {
+                for &key in keys {
+                    buf.put_u16(key.into());
+                }
+            }
+            SvcParam::Alpn(ids) => {
+                for id in ids {
+                    let length = u8::try_from(id.len()).map_err(|_| {
+                        ResourceDataError::InvalidServiceParameter(SvcParamKey::Alpn)
+                    })?;
+                    buf.put_u8(length);
+                    buf.put_slice(id);
+                }
+            }
+            SvcParam::NoDefaultAlpn => {}
+            SvcParam::Port(port) => buf.put_u16(*port),
+            SvcParam::Ipv4Hint(ips) => {
+                for &ip in ips {
+                    buf.put_u32(ip.into());
+                }
+            }
+            SvcParam::Ech(value) | SvcParam::Unknown { value, .. } => buf.put_slice(value),
+            SvcParam::Ipv6Hint(ips) => {
+                for &ip in ips {
+                    buf.put_u128(ip.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the items of a value list separated by commas.
+fn write_list<T>(
+    f: &mut fmt::Formatter<'_>,
+    items: &[T],
+    mut write: impl FnMut(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            ','.fmt(f)?;
+        }
+        write(f, item)?;
+    }
+    Ok(())
+}
+
+/// Writes an opaque value, escaping the characters that would end it or split it into a list.
+fn write_escaped(f: &mut fmt::Formatter<'_>, value: &[u8]) -> fmt::Result {
+    for &c in value {
+        match c {
+            b'"' | b'\\' | b',' | b';' | b'(' | b')' => write!(f, "\\{}", c as char)?,
+            0x21..=0x7e => write!(f, "{}", c as char)?,
+            _ => write!(f, "\\{c:03}")?,
+        }
+    }
+    Ok(())
+}
+
+impl Display for SvcParam {
+    /// Presents the parameter as `key=value`, or only as `key` when its value is empty.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.key().fmt(f)?;
+        match self {
+            SvcParam::Mandatory(keys) => {
+                '='.fmt(f)?;
+                write_list(f, keys, |f, key| key.fmt(f))
+            }
+            SvcParam::Alpn(ids) => {
+                '='.fmt(f)?;
+                write_list(f, ids, |f, id| write_escaped(f, id))
+            }
+            SvcParam::NoDefaultAlpn => Ok(()),
+            SvcParam::Port(port) => write!(f, "={port}"),
+            SvcParam::Ipv4Hint(ips) => {
+                '='.fmt(f)?;
+                write_list(f, ips, |f, ip| ip.fmt(f))
+            }
+            SvcParam::Ech(value) => {
+                '='.fmt(f)?;
+                write_base64(f, value)
+            }
+            SvcParam::Ipv6Hint(ips) => {
+                '='.fmt(f)?;
+                write_list(f, ips, |f, ip| ip.fmt(f))
+            }
+            SvcParam::Unknown { value, .. } if value.is_empty() => Ok(()),
+            SvcParam::Unknown { value, .. } => {
+                '='.fmt(f)?;
+                write_escaped(f, value)
+            }
+        }
+    }
+}
+
+/// Splits a value list on the commas that aren't escaped.
+fn split_list(value: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (index, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ',' => {
+                items.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    items.push(&value[start..]);
+    items
+}
+
+/// Resolves the `\X` and `\DDD` escapes of a value.
+fn unescape(value: &str) -> Result<Vec<u8>, ResourceDataError> {
+    decode_escaped(value).ok_or_else(|| ResourceDataError::Syntax(value.to_owned()))
+}
+
+impl FromStr for SvcParam {
+    type Err = ResourceDataError;
+
+    /// Parses a `key=value` or `key` token, where the value may be enclosed in double quotes.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ResourceDataError::Syntax;
+
+        let (key, value) = s.split_once('=').unwrap_or((s, ""));
+        let key = key.parse::<SvcParamKey>()?;
+        let value = match value.strip_prefix('"') {
+            Some(quoted) => quoted
+                .strip_suffix('"')
+                .ok_or_else(|| Syntax(s.to_owned()))?,
+            None => value,
+        };
+
+        let items = || {
+            split_list(value)
+                .into_iter()
+                .filter(|item| !item.is_empty())
+        };
+
+        let param = match key {
+            SvcParamKey::Mandatory => {
+                let mut keys = items().map(str::parse).collect::<Result<Vec<_>, _>>()?;
+                keys.sort_by_key(|&key| u16::from(key));
+                keys.dedup();
+                SvcParam::Mandatory(keys)
+            }
+            SvcParamKey::Alpn => SvcParam::Alpn(
+                items()
+                    .map(|id| match unescape(id)? {
+                        id if id.len() <= u8::MAX as usize => Ok(id),
+                        _ => Err(Syntax(s.to_owned())),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            SvcParamKey::NoDefaultAlpn if value.is_empty() => SvcParam::NoDefaultAlpn,
+            SvcParamKey::Port => SvcParam::Port(value.parse().map_err(|_| Syntax(s.to_owned()))?),
+            SvcParamKey::Ipv4Hint => SvcParam::Ipv4Hint(
+                items()
+                    .map(|ip| ip.parse().map_err(|_| Syntax(ip.to_owned())))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SvcParamKey::Ech => {
+                SvcParam::Ech(decode_base64(value).ok_or_else(|| Syntax(value.to_owned()))?)
+            }
+            SvcParamKey::Ipv6Hint => SvcParam::Ipv6Hint(
+                items()
+                    .map(|ip| ip.parse().map_err(|_| Syntax(ip.to_owned())))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SvcParamKey::Unknown(key) => SvcParam::Unknown {
+                key,
+                value: unescape(value)?,
+            },
+            SvcParamKey::NoDefaultAlpn => return Err(Syntax(s.to_owned())),
+        };
+
+        // Catches the empty lists, which don't have a wire format
+        let mut buf = vec![];
+        param.write_value(&mut buf)?;
+        SvcParam::parse(key, &buf)
+    }
+}
+
+/// The service parameters of a record, kept ordered by key.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SvcParams(BTreeMap<u16, SvcParam>);
+
+impl SvcParams {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn get(&self, key: SvcParamKey) -> Option<&SvcParam> {
+        self.0.get(&key.into())
+    }
+
+    /// Add `param`, returning the parameter it replaced if any
+    pub fn insert(&mut self, param: SvcParam) -> Option<SvcParam> {
+        self.0.insert(param.key().into(), param)
+    }
+
+    pub fn remove(&mut self, key: SvcParamKey) -> Option<SvcParam> {
+        self.0.remove(&key.into())
+    }
+
+    /// The parameters in increasing key order
+    pub fn iter(&self) -> impl Iterator<Item = &SvcParam> {
+        self.0.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Checks the rules of [RFC9460 8]: the keys listed by `mandatory` must be present, and no
+    /// key may be the invalid key 65535. The value of each parameter must also fit its length.
+    ///
+    /// Parsed parameters are always valid, while built ones should be checked before use.
+    ///
+    /// [RFC9460 8]: <https://datatracker.ietf.org/doc/html/rfc9460#section-8>
+    pub fn validate(&self) -> Result<(), ResourceDataError> {
+        for (&key, param) in self.0.iter() {
+            self.value_length(key, param)?;
+        }
+
+        Ok(())
+    }
+
+    /// The length of the value of `param`, once checked against the rules of
+    /// [`validate`][Self::validate].
+    fn value_length(&self, key: u16, param: &SvcParam) -> Result<u16, ResourceDataError> {
+        let invalid = ResourceDataError::InvalidServiceParameter(key.into());
+        let mut length = Counter::new();
+        param
+            .write_value(&mut length)
+            .map_err(|_| invalid.clone())?;
+
+        let valid = match param {
+            _ if key == u16::MAX => false,
+            SvcParam::Mandatory(keys) => keys.iter().all(|&listed| {
+                listed != SvcParamKey::Mandatory && self.0.contains_key(&listed.into())
+            }),
+            _ => true,
+        };
+        match u16::try_from(length.offset()) {
+            Ok(length) if valid => Ok(length),
+            _ => Err(invalid),
+        }
+    }
+
+    /// Parses the parameters filling `value`, which must be in strictly increasing key order, see
+    /// [`validate`][Self::validate].
+    pub fn parse(value: &[u8]) -> Result<Self, ResourceDataError> {
+        let mut buf = value;
+        let mut params = BTreeMap::new();
+        let mut last_key = None;
+
+        while !buf.is_empty() {
+            let [k0, k1, l0, l1] = take(&mut buf).ok_or(ResourceDataError::Truncated)?;
+            let key = u16::from_be_bytes([k0, k1]);
+            let length = u16::from_be_bytes([l0, l1]) as usize;
+
+            if last_key.is_some_and(|last| last >= key) {
+                return Err(ResourceDataError::InvalidServiceParameter(key.into()));
+            }
+            last_key = Some(key);
+
+            let data = buf.get(..length).ok_or(ResourceDataError::Truncated)?;
+            buf = &buf[length..];
+            params.insert(key, SvcParam::parse(key.into(), data)?);
+        }
+
+        let params = Self(params);
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Appends the wire format of the parameters to `buf`, failing on the first one that isn't
+    /// valid, see [`validate`][Self::validate].
+    pub fn write(&self, buf: &mut impl Sink) -> Result<(), ResourceDataError> {
+        for (&key, param) in self.0.iter() {
+            let length = self.value_length(key, param)?;
+            buf.put_u16(key);
+            buf.put_u16(length);
+            param.write_value(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromIterator<SvcParam> for SvcParams {
+    fn from_iter<T: IntoIterator<Item = SvcParam>>(iter: T) -> Self {
+        let mut params = Self::new();
+        for param in iter {
+            params.insert(param);
+        }
+        params
+    }
+}
+
+impl Display for SvcParams {
+    /// Lists the parameters separated by spaces.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, param) in self.iter().enumerate() {
+            if index > 0 {
+                " ".fmt(f)?;
+            }
+            param.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SvcParams {
+    type Err = ResourceDataError;
+
+    /// Parses whitespace separated parameters, which may be given in any order but only once, see
+    /// [`validate`][SvcParams::validate].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Self::new();
+        let mut token = String::new();
+        let mut quoted = false;
+        let mut escaped = false;
+
+        for c in s.chars().chain([' ']) {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => quoted = !quoted,
+                _ if c.is_whitespace() && !quoted => {
+                    if !token.is_empty() {
+                        let param = token.parse::<SvcParam>()?;
+                        if let Some(param) = params.insert(param) {
+                            return Err(ResourceDataError::InvalidServiceParameter(param.key()));
+                        }
+                        token.clear();
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            token.push(c);
+        }
+
+        if quoted {
+            return Err(ResourceDataError::Syntax(s.to_owned()));
+        }
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+impl ResourceData {
+    pub(super) fn parse_service_binding(
+        value: &[u8],
+        https: bool,
+    ) -> Result<ResourceData, ResourceDataError> {
+        let mut buf = value;
+        let priority = u16::from_be_bytes(take(&mut buf).ok_or(ResourceDataError::Truncated)?);
+        let (target, offset) = parse_label(buf)?;
+        let params = SvcParams::parse(&buf[offset..])?;
+
+        Ok(match https {
+            true => Self::HttpsServiceBinding {
+                priority,
+                target,
+                params,
+            },
+            false => Self::ServiceBinding {
+                priority,
+                target,
+                params,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod service_parameters {
+    use super::*;
+    use crate::message::{Label, ResourceType};
+
+    #[test]
+    fn round_trip_https() {
+        #[rustfmt::skip]
+        let buf = [
+            0, 1,
+            0,
+            0, 1, 0, 6, 2, b'h', b'2', 2, b'h', b'3',
+            0, 3, 0, 2, 0x01, 0xbb,
+            0, 4, 0, 4, 192, 0, 2, 1,
+        ];
+
+        let data = ResourceData::parse(ResourceType::HTTPS, &buf).unwrap();
+        assert_eq!(
+            data,
+            ResourceData::HttpsServiceBinding {
+                priority: 1,
+                target: Label::default(),
+                params: [
+                    SvcParam::Port(443),
+                    SvcParam::Alpn(vec![b"h2".to_vec(), b"h3".to_vec()]),
+                    SvcParam::Ipv4Hint(vec![Ipv4Addr::new(192, 0, 2, 1)]),
+                ]
+                .into_iter()
+                .collect(),
+            }
+        );
+        assert_eq!(
+            data.to_string(),
+            "1 . alpn=h2,h3 port=443 ipv4hint=192.0.2.1"
+        );
+        assert_eq!(Vec::try_from(data).unwrap(), buf);
+    }
+
+    #[test]
+    fn reject_unordered_keys() {
+        #[rustfmt::skip]
+        let buf = [
+            0, 3, 0, 2, 0x01, 0xbb,
+            0, 1, 0, 3, 2, b'h', b'2',
+        ];
+        assert_eq!(
+            SvcParams::parse(&buf),
+            Err(ResourceDataError::InvalidServiceParameter(
+                SvcParamKey::Alpn
+            ))
+        );
+        assert_eq!(
+            SvcParams::parse(&[0, 3, 0, 1, 0x01]),
+            Err(ResourceDataError::InvalidServiceParameter(
+                SvcParamKey::Port
+            ))
+        );
+    }
+
+    #[test]
+    fn mandatory_keys_are_present() {
+        #[rustfmt::skip]
+        let buf = [
+            0, 0, 0, 2, 0, 1,
+            0, 3, 0, 2, 0x01, 0xbb,
+        ];
+        assert_eq!(
+            SvcParams::parse(&buf),
+            Err(ResourceDataError::InvalidServiceParameter(
+                SvcParamKey::Mandatory
+            ))
+        );
+
+        let params = [
+            SvcParam::Mandatory(vec![SvcParamKey::Port]),
+            SvcParam::Port(443),
+        ]
+        .into_iter()
+        .collect::<SvcParams>();
+        assert_eq!(params.validate(), Ok(()));
+        let mut buf = vec![];
+        params.write(&mut buf).unwrap();
+        assert_eq!(SvcParams::parse(&buf), Ok(params));
+
+        assert_eq!(
+            SvcParams::parse(&[0xff, 0xff, 0, 0]),
+            Err(ResourceDataError::InvalidServiceParameter(
+                SvcParamKey::Unknown(u16::MAX)
+            ))
+        );
+
+        let id = "a".repeat(256);
+        assert!(format!("alpn={id}").parse::<SvcParams>().is_err());
+    }
+
+    #[test]
+    fn invalid_parameters_are_not_written() {
+        let mut buf = vec![];
+        for param in [
+            SvcParam::Alpn(vec![vec![b'a'; 256]]),
+            SvcParam::Ech(vec![0; u16::MAX as usize + 1]),
+            SvcParam::Mandatory(vec![SvcParamKey::Port]),
+        ] {
+            let key = param.key();
+            let params = [param].into_iter().collect::<SvcParams>();
+            assert_eq!(
+                params.write(&mut buf),
+                Err(ResourceDataError::InvalidServiceParameter(key))
+            );
+        }
+    }
+
+    #[test]
+    fn presentation() {
+        let params = r#"key65333="a b" port=8443 mandatory=port,alpn alpn="h2,h\,x" ipv6hint=2001:db8::1 no-default-alpn ech=Zm9vYmFy"#
+            .parse::<SvcParams>()
+            .unwrap();
+
+        assert_eq!(params.get(SvcParamKey::Port), Some(&SvcParam::Port(8443)));
+        assert_eq!(
+            params.get(SvcParamKey::Alpn),
+            Some(&SvcParam::Alpn(vec![b"h2".to_vec(), b"h,x".to_vec()]))
+        );
+        assert_eq!(
+            params.to_string(),
+            r"mandatory=alpn,port alpn=h2,h\,x no-default-alpn port=8443 ech=Zm9vYmFy ipv6hint=2001:db8::1 key65333=a\032b"
+        );
+        assert_eq!(params.to_string().parse(), Ok(params));
+    }
+
+    #[test]
+    fn invalid_presentation() {
+        for text in [
+            "port",
+            "port=1 port=2",
+            "alpn=",
+            "no-default-alpn=x",
+            "key65536",
+            "ipv4hint=::1",
+            "alpn=\"h2",
+            "mandatory=mandatory",
+            "mandatory=alpn port=443",
+            "key65535",
+        ] {
+            assert!(text.parse::<SvcParams>().is_err(), "{text}");
+        }
+    }
+}
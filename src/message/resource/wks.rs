@@ -0,0 +1,201 @@
+//! The bit map of a [`WKS`] record has one bit per port of the specified protocol, starting with
+//! the most significant bit of the first octet for port 0.
+//!
+//! [`WKS`]: super::ResourceData::WKS
+use std::fmt::{self, Display};
+
+/// IP protocol numbers and mnemonics of [RFC1010], limited to the ones a WKS record is useful for.
+///
+/// [RFC1010]: <https://datatracker.ietf.org/doc/html/rfc1010>
+const PROTOCOLS: [(u8, &str); 2] = [(6, "TCP"), (17, "UDP")];
+
+/// Port numbers and mnemonics of well known services.
+const SERVICES: [(u16, &str); 16] = [
+    (7, "echo"),
+    (20, "ftp-data"),
+    (21, "ftp"),
+    (22, "ssh"),
+    (23, "telnet"),
+    (25, "smtp"),
+    (37, "time"),
+    (43, "whois"),
+    (53, "domain"),
+    (69, "tftp"),
+    (79, "finger"),
+    (80, "http"),
+    (110, "pop3"),
+    (119, "nntp"),
+    (123, "ntp"),
+    (143, "imap"),
+];
+
+/// The mnemonic of the IP `protocol`, if any.
+pub fn protocol_name(protocol: u8) -> Option<&'static str> {
+    PROTOCOLS
+        .iter()
+        .find(|(number, _)| *number == protocol)
+        .map(|(_, name)| *name)
+}
+
+/// The IP protocol with the (case insensitive) mnemonic `name`, if any.
+pub fn protocol_number(name: &str) -> Option<u8> {
+    PROTOCOLS
+        .iter()
+        .find(|(_, mnemonic)| mnemonic.eq_ignore_ascii_case(name))
+        .map(|(number, _)| *number)
+}
+
+/// The mnemonic of the well known service listening on `port`, if any.
+pub fn service_name(port: u16) -> Option<&'static str> {
+    SERVICES
+        .iter()
+        .find(|(number, _)| *number == port)
+        .map(|(_, name)| *name)
+}
+
+/// The port of the well known service with the (case insensitive) mnemonic `name`, if any.
+pub fn service_port(name: &str) -> Option<u16> {
+    SERVICES
+        .iter()
+        .find(|(_, mnemonic)| mnemonic.eq_ignore_ascii_case(name))
+        .map(|(number, _)| *number)
+}
+
+/// The number of octets holding the bits of every port, from 0 to 65535.
+const MAX_BITMAP_LENGTH: usize = (u16::MAX as usize + 1) / 8;
+
+/// A set of ports, stored in the wire format of the WKS bit map.
+///
+/// The octets are kept as they were received, so that trailing zero octets survive a round trip,
+/// while those past the bit of port 65535 don't stand for any port.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PortBitmap(Vec<u8>);
+
+impl PortBitmap {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Whether the bit of `port` is set
+    pub fn contains(&self, port: u16) -> bool {
+        let (index, mask) = Self::position(port);
+        self.0.get(index).is_some_and(|octet| octet & mask != 0)
+    }
+
+    /// Set the bit of `port`, growing the bit map when needed
+    pub fn insert(&mut self, port: u16) {
+        let (index, mask) = Self::position(port);
+        if self.0.len() <= index {
+            self.0.resize(index + 1, 0);
+        }
+        self.0[index] |= mask;
+    }
+
+    /// Clear the bit of `port`
+    pub fn remove(&mut self, port: u16) {
+        let (index, mask) = Self::position(port);
+        if let Some(octet) = self.0.get_mut(index) {
+            *octet &= !mask;
+        }
+    }
+
+    /// The ports whose bit is set, in ascending order
+    pub fn ports(&self) -> impl Iterator<Item = u16> + '_ {
+        let octets = &self.0[..self.0.len().min(MAX_BITMAP_LENGTH)];
+        octets.iter().enumerate().flat_map(|(index, octet)| {
+            (0..8)
+                .filter(move |bit| octet & (0b1000_0000 >> bit) != 0)
+                .map(move |bit| (index * 8 + bit) as u16)
+        })
+    }
+
+    /// The wire format of the bit map
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn position(port: u16) -> (usize, u8) {
+        (port as usize / 8, 0b1000_0000 >> (port % 8))
+    }
+}
+
+impl From<Vec<u8>> for PortBitmap {
+    fn from(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<PortBitmap> for Vec<u8> {
+    fn from(value: PortBitmap) -> Self {
+        value.0
+    }
+}
+
+impl FromIterator<u16> for PortBitmap {
+    fn from_iter<T: IntoIterator<Item = u16>>(iter: T) -> Self {
+        let mut bit_map = Self::new();
+        for port in iter {
+            bit_map.insert(port);
+        }
+        bit_map
+    }
+}
+
+impl Display for PortBitmap {
+    /// Lists the ports separated by spaces, using the mnemonics of well known services.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, port) in self.ports().enumerate() {
+            if index > 0 {
+                " ".fmt(f)?;
+            }
+            match service_name(port) {
+                Some(name) => name.fmt(f)?,
+                None => port.fmt(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod bit_map {
+    use super::*;
+
+    #[test]
+    fn insert_and_remove_ports() {
+        let mut bit_map = PortBitmap::new();
+        bit_map.insert(25);
+        bit_map.insert(0);
+        bit_map.insert(25);
+
+        assert_eq!(bit_map.as_bytes(), [0b1000_0000, 0, 0, 0b0100_0000]);
+        assert!(bit_map.contains(0));
+        assert!(bit_map.contains(25));
+        assert!(!bit_map.contains(24));
+        assert!(!bit_map.contains(65535));
+
+        bit_map.remove(0);
+        bit_map.remove(1024);
+        assert_eq!(bit_map.ports().collect::<Vec<_>>(), [25]);
+    }
+
+    #[test]
+    fn ports_end_at_65535() {
+        let mut octets = vec![0; MAX_BITMAP_LENGTH];
+        octets[0] = 0b1000_0000;
+        octets[MAX_BITMAP_LENGTH - 1] = 0b0000_0001;
+        octets.push(0b1000_0000);
+
+        let bit_map = PortBitmap::from(octets);
+        assert_eq!(bit_map.ports().collect::<Vec<_>>(), [0, 65535]);
+        assert_eq!(bit_map.as_bytes().len(), MAX_BITMAP_LENGTH + 1);
+    }
+
+    #[test]
+    fn present_services() {
+        let bit_map = [21, 25, 8080].into_iter().collect::<PortBitmap>();
+        assert_eq!(bit_map.to_string(), "ftp smtp 8080");
+        assert!(bit_map.contains(8080));
+        assert_eq!(bit_map.as_bytes().len(), 8080 / 8 + 1);
+    }
+}
@@ -1,13 +1,18 @@
+//! Types and classes are open-ended: codes without a registered mnemonic are kept as
+//! `Unknown(code)` and presented in the generic `TYPE<code>` and `CLASS<code>` forms of [RFC3597
+//! 5], so that records of unknown types are preserved instead of rejected.
+//!
+//! [RFC3597 5]: <https://datatracker.ietf.org/doc/html/rfc3597#section-5>
 use std::{
     error::Error,
     fmt::{self, Display},
+    str::FromStr,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u8)]
 pub enum ResourceType {
     /// A host address
-    A = 1,
+    A,
 
     /// An authoritative name server
     NS,
@@ -53,13 +58,66 @@ pub enum ResourceType {
 
     /// Text strings
     TXT,
+
+    /// An IPv6 host address
+    AAAA,
+
+    /// The location of the server(s) for a specific protocol and domain
+    SRV,
+
+    /// The names of the services a domain offers, as rewrite rules
+    NAPTR,
+
+    /// An EDNS(0) pseudo record, carrying the extended capabilities of a requestor
+    OPT,
+
+    /// A delegation signer, referring to a DNSKEY of a child zone
+    DS,
+
+    /// The fingerprint of an SSH host key
+    SSHFP,
+
+    /// A signature over an RRset
+    RRSIG,
+
+    /// The next owner name and the types existing at the owner name, in canonical order
+    NSEC,
+
+    /// A public key used to verify signatures
+    DNSKEY,
+
+    /// The hashed next owner name and the types existing at the owner name
+    NSEC3,
+
+    /// The parameters used to compute the hashed owner names of a zone
+    NSEC3PARAM,
+
+    /// The certificate or public key of a TLS server
+    TLSA,
+
+    /// The endpoint of a service and the parameters to access it
+    SVCB,
+
+    /// The endpoint and parameters of an HTTPS origin
+    HTTPS,
+
+    /// The certification authorities allowed to issue certificates for a domain
+    CAA,
+
+    /// All types at a name, which is only found in the prerequisite and update sections of
+    /// dynamic updates ([RFC2136 2.4])
+    ///
+    /// [RFC2136 2.4]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2.4>
+    ANY,
+
+    /// A type without a registered mnemonic
+    Unknown(u16),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u8)]
 pub enum QuestionType {
     /// A host address
-    A = 1,
+    A,
 
     /// An authoritative name server
     NS,
@@ -106,8 +164,53 @@ pub enum QuestionType {
     /// Text strings
     TXT,
 
+    /// An IPv6 host address
+    AAAA,
+
+    /// The location of the server(s) for a specific protocol and domain
+    SRV,
+
+    /// The names of the services a domain offers, as rewrite rules
+    NAPTR,
+
+    /// An EDNS(0) pseudo record, carrying the extended capabilities of a requestor
+    OPT,
+
+    /// A delegation signer, referring to a DNSKEY of a child zone
+    DS,
+
+    /// The fingerprint of an SSH host key
+    SSHFP,
+
+    /// A signature over an RRset
+    RRSIG,
+
+    /// The next owner name and the types existing at the owner name, in canonical order
+    NSEC,
+
+    /// A public key used to verify signatures
+    DNSKEY,
+
+    /// The hashed next owner name and the types existing at the owner name
+    NSEC3,
+
+    /// The parameters used to compute the hashed owner names of a zone
+    NSEC3PARAM,
+
+    /// The certificate or public key of a TLS server
+    TLSA,
+
+    /// The endpoint of a service and the parameters to access it
+    SVCB,
+
+    /// The endpoint and parameters of an HTTPS origin
+    HTTPS,
+
+    /// The certification authorities allowed to issue certificates for a domain
+    CAA,
+
     /// A request for a transfer of an entire zone
-    AXFR = 252,
+    AXFR,
 
     /// A request for mailbox-related records (MB, MG or MR)
     MAILB,
@@ -117,26 +220,27 @@ pub enum QuestionType {
 
     /// A request for all records
     ALL,
+
+    /// A type without a registered mnemonic
+    Unknown(u16),
 }
 
-/// No type has been registered with code
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct UnregisteredType(u16);
+/// No type has been registered with mnemonic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnregisteredType(String);
 
 impl Display for UnregisteredType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        format!("no type has been registered with code '{}'", self.0).fmt(f)
+        format!("no type has been registered with mnemonic '{}'", self.0).fmt(f)
     }
 }
 
 impl Error for UnregisteredType {}
 
-impl TryFrom<u16> for ResourceType {
-    type Error = UnregisteredType;
-
-    fn try_from(value: u16) -> Result<Self, Self::Error> {
+impl From<u16> for ResourceType {
+    fn from(value: u16) -> Self {
         use ResourceType::*;
-        Ok(match value {
+        match value {
             1 => A,
             2 => NS,
             3 => MD,
@@ -153,17 +257,72 @@ impl TryFrom<u16> for ResourceType {
             14 => MINFO,
             15 => MX,
             16 => TXT,
-            code => return Err(UnregisteredType(code)),
-        })
+            28 => AAAA,
+            33 => SRV,
+            35 => NAPTR,
+            41 => OPT,
+            43 => DS,
+            44 => SSHFP,
+            46 => RRSIG,
+            47 => NSEC,
+            48 => DNSKEY,
+            50 => NSEC3,
+            51 => NSEC3PARAM,
+            52 => TLSA,
+            64 => SVCB,
+            65 => HTTPS,
+            257 => CAA,
+            255 => ANY,
+            code => Unknown(code),
+        }
     }
 }
 
-impl TryFrom<u16> for QuestionType {
-    type Error = UnregisteredType;
+impl From<ResourceType> for u16 {
+    fn from(value: ResourceType) -> Self {
+        use ResourceType::*;
+        match value {
+            A => 1,
+            NS => 2,
+            MD => 3,
+            MF => 4,
+            CNAME => 5,
+            SOA => 6,
+            MB => 7,
+            MG => 8,
+            MR => 9,
+            NULL => 10,
+            WKS => 11,
+            PTR => 12,
+            HINFO => 13,
+            MINFO => 14,
+            MX => 15,
+            TXT => 16,
+            AAAA => 28,
+            SRV => 33,
+            NAPTR => 35,
+            OPT => 41,
+            DS => 43,
+            SSHFP => 44,
+            RRSIG => 46,
+            NSEC => 47,
+            DNSKEY => 48,
+            NSEC3 => 50,
+            NSEC3PARAM => 51,
+            TLSA => 52,
+            SVCB => 64,
+            HTTPS => 65,
+            CAA => 257,
+            ANY => 255,
+            Unknown(code) => code,
+        }
+    }
+}
 
-    fn try_from(value: u16) -> Result<Self, Self::Error> {
+impl From<u16> for QuestionType {
+    fn from(value: u16) -> Self {
         use QuestionType::*;
-        Ok(match value {
+        match value {
             1 => A,
             2 => NS,
             3 => MD,
@@ -180,32 +339,183 @@ impl TryFrom<u16> for QuestionType {
             14 => MINFO,
             15 => MX,
             16 => TXT,
+            28 => AAAA,
+            33 => SRV,
+            35 => NAPTR,
+            41 => OPT,
+            43 => DS,
+            44 => SSHFP,
+            46 => RRSIG,
+            47 => NSEC,
+            48 => DNSKEY,
+            50 => NSEC3,
+            51 => NSEC3PARAM,
+            52 => TLSA,
+            64 => SVCB,
+            65 => HTTPS,
+            257 => CAA,
             252 => AXFR,
             253 => MAILB,
             254 => MAILA,
             255 => ALL,
-            code => return Err(UnregisteredType(code)),
+            code => Unknown(code),
+        }
+    }
+}
+
+impl From<QuestionType> for u16 {
+    fn from(value: QuestionType) -> Self {
+        use QuestionType::*;
+        match value {
+            A => 1,
+            NS => 2,
+            MD => 3,
+            MF => 4,
+            CNAME => 5,
+            SOA => 6,
+            MB => 7,
+            MG => 8,
+            MR => 9,
+            NULL => 10,
+            WKS => 11,
+            PTR => 12,
+            HINFO => 13,
+            MINFO => 14,
+            MX => 15,
+            TXT => 16,
+            AAAA => 28,
+            SRV => 33,
+            NAPTR => 35,
+            OPT => 41,
+            DS => 43,
+            SSHFP => 44,
+            RRSIG => 46,
+            NSEC => 47,
+            DNSKEY => 48,
+            NSEC3 => 50,
+            NSEC3PARAM => 51,
+            TLSA => 52,
+            SVCB => 64,
+            HTTPS => 65,
+            CAA => 257,
+            AXFR => 252,
+            MAILB => 253,
+            MAILA => 254,
+            ALL => 255,
+            Unknown(code) => code,
+        }
+    }
+}
+
+impl Display for ResourceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceType::Unknown(code) => write!(f, "TYPE{code}"),
+            typ => write!(f, "{typ:?}"),
+        }
+    }
+}
+
+impl Display for QuestionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuestionType::ALL => "ANY".fmt(f),
+            QuestionType::Unknown(code) => write!(f, "TYPE{code}"),
+            typ => write!(f, "{typ:?}"),
+        }
+    }
+}
+
+/// Parses the `<prefix><code>` generic form of [RFC3597 5].
+///
+/// [RFC3597 5]: <https://datatracker.ietf.org/doc/html/rfc3597#section-5>
+fn parse_generic(prefix: &str, value: &str) -> Option<u16> {
+    // `get` also fails when the prefix doesn't end on a character boundary of `value`
+    let head = value.get(..prefix.len())?;
+    let code = &value[prefix.len()..];
+    match head.eq_ignore_ascii_case(prefix) && code.bytes().all(|c| c.is_ascii_digit()) {
+        true => code.parse().ok(),
+        false => None,
+    }
+}
+
+impl FromStr for ResourceType {
+    type Err = UnregisteredType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ResourceType::*;
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "A" => A,
+            "NS" => NS,
+            "MD" => MD,
+            "MF" => MF,
+            "CNAME" => CNAME,
+            "SOA" => SOA,
+            "MB" => MB,
+            "MG" => MG,
+            "MR" => MR,
+            "NULL" => NULL,
+            "WKS" => WKS,
+            "PTR" => PTR,
+            "HINFO" => HINFO,
+            "MINFO" => MINFO,
+            "MX" => MX,
+            "TXT" => TXT,
+            "AAAA" => AAAA,
+            "SRV" => SRV,
+            "NAPTR" => NAPTR,
+            "OPT" => OPT,
+            "DS" => DS,
+            "SSHFP" => SSHFP,
+            "RRSIG" => RRSIG,
+            "NSEC" => NSEC,
+            "DNSKEY" => DNSKEY,
+            "NSEC3" => NSEC3,
+            "NSEC3PARAM" => NSEC3PARAM,
+            "TLSA" => TLSA,
+            "SVCB" => SVCB,
+            "HTTPS" => HTTPS,
+            "CAA" => CAA,
+            "ANY" => ANY,
+            _ => match parse_generic("TYPE", s) {
+                Some(code) => code.into(),
+                None => return Err(UnregisteredType(s.to_owned())),
+            },
         })
     }
 }
 
-/// No class has been registered with code
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct UnregisteredClass(u16);
+impl FromStr for QuestionType {
+    type Err = UnregisteredType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use QuestionType::*;
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "AXFR" => AXFR,
+            "MAILB" => MAILB,
+            "MAILA" => MAILA,
+            "ANY" | "*" => ALL,
+            _ => u16::from(s.parse::<ResourceType>()?).into(),
+        })
+    }
+}
+
+/// No class has been registered with mnemonic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnregisteredClass(String);
 
 impl Display for UnregisteredClass {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        format!("no class has been registered with code '{}'", self.0).fmt(f)
+        format!("no class has been registered with mnemonic '{}'", self.0).fmt(f)
     }
 }
 
 impl Error for UnregisteredClass {}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u8)]
 pub enum ResourceClass {
     /// The Internet
-    IN = 1,
+    IN,
 
     /// The CSNET class (OBSOLETE - used only for examples in some obsolete RFCs)
     CS,
@@ -215,13 +525,27 @@ pub enum ResourceClass {
 
     /// Hesiod [Dyer 87]
     HS,
+
+    /// No class, which is only found in the prerequisite and update sections of dynamic updates
+    /// ([RFC2136 2.4])
+    ///
+    /// [RFC2136 2.4]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2.4>
+    NONE,
+
+    /// Any class, which is only found in the prerequisite and update sections of dynamic updates
+    /// ([RFC2136 2.4])
+    ///
+    /// [RFC2136 2.4]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2.4>
+    ANY,
+
+    /// A class without a registered mnemonic
+    Unknown(u16),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u8)]
 pub enum QuestionClass {
     /// The Internet
-    IN = 1,
+    IN,
 
     /// The CSNET class (OBSOLETE - used only for examples in some obsolete RFCs)
     CS,
@@ -232,37 +556,153 @@ pub enum QuestionClass {
     /// Hesiod [Dyer 87]
     HS,
 
+    /// No class
+    NONE,
+
     /// A request for all records
-    Any = 255,
-}
+    Any,
 
-impl TryFrom<u16> for ResourceClass {
-    type Error = UnregisteredClass;
+    /// A class without a registered mnemonic
+    Unknown(u16),
+}
 
-    fn try_from(value: u16) -> Result<Self, Self::Error> {
+impl From<u16> for ResourceClass {
+    fn from(value: u16) -> Self {
         use ResourceClass::*;
-        Ok(match value {
+        match value {
             1 => IN,
             2 => CS,
             3 => CH,
             4 => HS,
-            code => return Err(UnregisteredClass(code)),
-        })
+            254 => NONE,
+            255 => ANY,
+            code => Unknown(code),
+        }
     }
 }
 
-impl TryFrom<u16> for QuestionClass {
-    type Error = UnregisteredClass;
+impl From<ResourceClass> for u16 {
+    fn from(value: ResourceClass) -> Self {
+        use ResourceClass::*;
+        match value {
+            IN => 1,
+            CS => 2,
+            CH => 3,
+            HS => 4,
+            NONE => 254,
+            ANY => 255,
+            Unknown(code) => code,
+        }
+    }
+}
 
-    fn try_from(value: u16) -> Result<Self, Self::Error> {
+impl From<u16> for QuestionClass {
+    fn from(value: u16) -> Self {
         use QuestionClass::*;
-        Ok(match value {
+        match value {
             1 => IN,
             2 => CS,
             3 => CH,
             4 => HS,
+            254 => NONE,
             255 => Any,
-            code => return Err(UnregisteredClass(code)),
+            code => Unknown(code),
+        }
+    }
+}
+
+impl From<QuestionClass> for u16 {
+    fn from(value: QuestionClass) -> Self {
+        use QuestionClass::*;
+        match value {
+            IN => 1,
+            CS => 2,
+            CH => 3,
+            HS => 4,
+            NONE => 254,
+            Any => 255,
+            Unknown(code) => code,
+        }
+    }
+}
+
+impl Display for ResourceClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceClass::Unknown(code) => write!(f, "CLASS{code}"),
+            class => write!(f, "{class:?}"),
+        }
+    }
+}
+
+impl Display for QuestionClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuestionClass::Any => "ANY".fmt(f),
+            QuestionClass::Unknown(code) => write!(f, "CLASS{code}"),
+            class => write!(f, "{class:?}"),
+        }
+    }
+}
+
+impl FromStr for ResourceClass {
+    type Err = UnregisteredClass;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ResourceClass::*;
+        Ok(match s.to_ascii_uppercase().as_str() {
+            "IN" => IN,
+            "CS" => CS,
+            "CH" => CH,
+            "HS" => HS,
+            "NONE" => NONE,
+            "ANY" => ANY,
+            _ => match parse_generic("CLASS", s) {
+                Some(code) => code.into(),
+                None => return Err(UnregisteredClass(s.to_owned())),
+            },
         })
     }
 }
+
+impl FromStr for QuestionClass {
+    type Err = UnregisteredClass;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "ANY" | "*" => Ok(QuestionClass::Any),
+            _ => Ok(u16::from(s.parse::<ResourceClass>()?).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod presentation {
+    use super::*;
+
+    #[test]
+    fn unknown_codes_round_trip() {
+        assert_eq!(ResourceType::from(16), ResourceType::TXT);
+        assert_eq!(ResourceType::from(1234), ResourceType::Unknown(1234));
+        assert_eq!(u16::from(ResourceType::Unknown(1234)), 1234);
+        assert_eq!(QuestionClass::from(255), QuestionClass::Any);
+        assert_eq!(u16::from(ResourceClass::from(4096)), 4096);
+    }
+
+    #[test]
+    fn generic_mnemonics() {
+        assert_eq!(ResourceType::Unknown(731).to_string(), "TYPE731");
+        assert_eq!(ResourceClass::Unknown(32).to_string(), "CLASS32");
+        assert_eq!("type731".parse(), Ok(ResourceType::Unknown(731)));
+        assert_eq!("TYPE1".parse(), Ok(ResourceType::A));
+        assert_eq!("mx".parse(), Ok(QuestionType::MX));
+        assert_eq!("*".parse(), Ok(QuestionType::ALL));
+        assert_eq!("CLASS1".parse(), Ok(ResourceClass::IN));
+        assert_eq!("ANY".parse(), Ok(QuestionClass::Any));
+
+        assert!("TYPE".parse::<ResourceType>().is_err());
+        assert!("TYPE+1".parse::<ResourceType>().is_err());
+        assert!("TYPE65536".parse::<ResourceType>().is_err());
+        assert!("BOGUS".parse::<ResourceClass>().is_err());
+    }
+}
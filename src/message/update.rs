@@ -0,0 +1,235 @@
+//! A dynamic [`update`] message ([RFC2136 2]) shares the format of every other message, but
+//! renames its sections:
+//!
+//! ```txt
+//!     +---------------------+
+//!     |        Header       |
+//!     +---------------------+
+//!     |         Zone        | specifies the zone to be updated
+//!     +---------------------+
+//!     |     Prerequisite    | RRs or RRsets which must (not) preexist
+//!     +---------------------+
+//!     |        Update       | RRs or RRsets to be added or deleted
+//!     +---------------------+
+//!     |   Additional Data   | additional data
+//!     +---------------------+
+//! ```
+//!
+//! The zone section holds a single entry in the format of a [`Question`], whose type must be SOA.
+//!
+//! [`update`]: super::header::OperationCode::Update
+//! [RFC2136 2]: <https://datatracker.ietf.org/doc/html/rfc2136#section-2>
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use super::{Message, OperationCode, Question, QuestionType, ResourceRecord};
+
+/// A view of a [`Message`] with the [`Update`][OperationCode::Update] operation code, naming its
+/// sections after their use in dynamic updates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Update<'a> {
+    message: &'a Message,
+}
+
+impl<'a> Update<'a> {
+    /// The zone whose records are to be updated, its class is the class of the zone.
+    pub fn zone(&self) -> &'a Question {
+        &self.message.questions[0]
+    }
+
+    /// The records that must (not) exist for the update to proceed, stored in the
+    /// [`answers`][Message::answers].
+    pub fn prerequisites(&self) -> &'a [ResourceRecord] {
+        &self.message.answers
+    }
+
+    /// The records to be added to or deleted from the zone, stored in the
+    /// [`authorities`][Message::authorities].
+    pub fn updates(&self) -> &'a [ResourceRecord] {
+        &self.message.authorities
+    }
+
+    /// Records relating to the update, e.g. glue for new name servers.
+    pub fn additionals(&self) -> &'a [ResourceRecord] {
+        &self.message.additionals
+    }
+
+    /// The underlying message
+    pub fn message(&self) -> &'a Message {
+        self.message
+    }
+}
+
+impl<'a> TryFrom<&'a Message> for Update<'a> {
+    type Error = UpdateError;
+
+    fn try_from(message: &'a Message) -> Result<Self, Self::Error> {
+        use UpdateError::*;
+
+        match message.header.operation_code {
+            OperationCode::Update => (),
+            code => return Err(NotUpdate(code)),
+        }
+
+        match message.questions.as_slice() {
+            [zone] if zone.typ == QuestionType::SOA => Ok(Self { message }),
+            [zone] => Err(ZoneType(zone.typ)),
+            zones => Err(ZoneCount(zones.len())),
+        }
+    }
+}
+
+impl Message {
+    /// Create a new dynamic update [`Message`] for `zone`, whose sections may then be filled
+    /// through [`require`][Self::require], [`update`][Self::update] and [`add`][Self::add]
+    pub fn new_update(id: u16, zone: Question) -> Self {
+        let mut message = Message::new(id);
+        message.query();
+        message.header.operation_code = OperationCode::Update;
+        message.questions.push(zone);
+        message
+    }
+
+    /// Add a [`ResourceRecord`] to the prerequisites of an update, i.e. the
+    /// [`answers`][Self::answers]
+    pub fn require(&mut self, rr: ResourceRecord) {
+        self.answer(rr);
+    }
+
+    /// Add a [`ResourceRecord`] to the updates of an update, i.e. the
+    /// [`authorities`][Self::authorities]
+    pub fn update(&mut self, rr: ResourceRecord) {
+        self.authorize(rr);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateError {
+    /// The message has another operation code
+    NotUpdate(OperationCode),
+    /// The zone section must hold exactly one entry
+    ZoneCount(usize),
+    /// The type of the zone entry must be SOA
+    ZoneType(QuestionType),
+}
+
+impl Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use UpdateError::*;
+        match self {
+            NotUpdate(code) => format!("expected an update message, but found '{code:?}'").fmt(f),
+            ZoneCount(count) => {
+                format!("the zone section must hold a single entry, but found '{count}'").fmt(f)
+            }
+            ZoneType(typ) => format!("the zone type must be SOA, but found '{typ}'").fmt(f),
+        }
+    }
+}
+
+impl Error for UpdateError {}
+
+#[cfg(test)]
+mod sections {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::message::{Label, QuestionClass, ResourceClass, ResourceData, ResourceType};
+
+    fn zone() -> Question {
+        Question {
+            name: Label::parse_str("example.com").unwrap(),
+            typ: QuestionType::SOA,
+            class: QuestionClass::IN,
+        }
+    }
+
+    #[test]
+    fn round_trip_update() {
+        let host = Label::parse_str("host.example.com").unwrap();
+
+        let mut message = Message::new_update(42, zone());
+        // "Name is not in use" prerequisite, see RFC2136 2.4.5
+        message.require(ResourceRecord {
+            name: host.clone(),
+            class: ResourceClass::NONE,
+            time_to_live: 0,
+            data: ResourceData::Empty(ResourceType::ANY),
+        });
+        message.update(ResourceRecord {
+            name: host.clone(),
+            class: ResourceClass::IN,
+            time_to_live: 300,
+            data: ResourceData::Address(Ipv4Addr::new(192, 0, 2, 7)),
+        });
+
+        let message = Message::try_from(&Vec::try_from(message).unwrap()[..]).unwrap();
+        let update = Update::try_from(&message).unwrap();
+
+        assert_eq!(update.zone(), &zone());
+        assert_eq!(update.prerequisites().len(), 1);
+        assert_eq!(update.prerequisites()[0].class, ResourceClass::NONE);
+        assert_eq!(update.updates().len(), 1);
+        assert_eq!(update.updates()[0].name, host);
+        assert!(update.additionals().is_empty());
+    }
+
+    #[test]
+    fn empty_data_of_deletions() {
+        let host = Label::parse_str("host.example.com").unwrap();
+
+        let mut message = Message::new_update(42, zone());
+        // "Delete an RRset" update, see RFC2136 2.5.2
+        message.update(ResourceRecord {
+            name: host.clone(),
+            class: ResourceClass::ANY,
+            time_to_live: 0,
+            data: ResourceData::Empty(ResourceType::A),
+        });
+        // "RRset does not exist" prerequisite, see RFC2136 2.4.3
+        message.require(ResourceRecord {
+            name: host.clone(),
+            class: ResourceClass::NONE,
+            time_to_live: 0,
+            data: ResourceData::Empty(ResourceType::AAAA),
+        });
+
+        let buf = Vec::try_from(message).unwrap();
+        // the RDLENGTH of the deletion is 0
+        assert_eq!(buf[buf.len() - 2..], [0, 0]);
+
+        let message = Message::try_from(&buf[..]).unwrap();
+        let update = Update::try_from(&message).unwrap();
+        assert_eq!(
+            update.updates()[0].data,
+            ResourceData::Empty(ResourceType::A)
+        );
+        assert_eq!(update.updates()[0].class, ResourceClass::ANY);
+        assert_eq!(update.prerequisites()[0].typ(), ResourceType::AAAA);
+
+        // data is still required in the other classes
+        assert!(ResourceData::parse_in_class(ResourceType::A, ResourceClass::IN, &[]).is_err());
+    }
+
+    #[test]
+    fn reject_malformed_zone_section() {
+        let mut message = Message::new(1);
+        assert_eq!(
+            Update::try_from(&message),
+            Err(UpdateError::NotUpdate(OperationCode::StandardQuery))
+        );
+
+        message.header.operation_code = OperationCode::Update;
+        assert_eq!(Update::try_from(&message), Err(UpdateError::ZoneCount(0)));
+
+        message.questions.push(Question {
+            typ: QuestionType::A,
+            ..zone()
+        });
+        assert_eq!(
+            Update::try_from(&message),
+            Err(UpdateError::ZoneType(QuestionType::A))
+        );
+    }
+}
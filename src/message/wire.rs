@@ -0,0 +1,318 @@
+//! Writing the wire format of messages into any [`BufMut`], such as a fixed `&mut [u8]` reused
+//! across responses, see [`Encode`].
+//!
+//! The writers of every part of a message append to a [`Sink`] instead of a buffer, which is
+//! either a `Vec<u8>`, a [`Writer`] checking the room left in a [`BufMut`], or a [`Counter`]
+//! measuring what would be written.
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use bytes::BufMut;
+
+use super::{
+    CharacterString, Header, Label, Message, Question, ResourceData, ResourceDataError,
+    ResourceRecord, Section,
+};
+
+/// The destination of the wire format of a message.
+pub trait Sink {
+    /// The number of octets written so far, which is the offset of the next one from the start
+    /// of the message when the sink holds a whole message.
+    fn offset(&self) -> usize;
+
+    fn put_slice(&mut self, bytes: &[u8]);
+
+    fn put_u8(&mut self, value: u8) {
+        self.put_slice(&[value]);
+    }
+
+    fn put_u16(&mut self, value: u16) {
+        self.put_slice(&value.to_be_bytes());
+    }
+
+    fn put_u32(&mut self, value: u32) {
+        self.put_slice(&value.to_be_bytes());
+    }
+
+    fn put_u128(&mut self, value: u128) {
+        self.put_slice(&value.to_be_bytes());
+    }
+}
+
+impl Sink for Vec<u8> {
+    fn offset(&self) -> usize {
+        self.len()
+    }
+
+    fn put_slice(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+/// A [`Sink`] counting the octets written into it, e.g. to learn the length of a record data
+/// before writing it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Counter(usize);
+
+impl Counter {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A counter whose offset starts at `offset`, e.g. to measure a part of a message whose names
+    /// are compressed, since whether a suffix may be pointed to depends on where it's written.
+    pub fn starting_at(offset: usize) -> Self {
+        Self(offset)
+    }
+}
+
+impl Sink for Counter {
+    fn offset(&self) -> usize {
+        self.0
+    }
+
+    fn put_slice(&mut self, bytes: &[u8]) {
+        self.0 += bytes.len();
+    }
+}
+
+/// A [`Sink`] writing into a [`BufMut`] as long as it has room left, and only counting the
+/// octets from the first one that doesn't fit on.
+#[derive(Debug)]
+pub struct Writer<'a, B> {
+    buf: &'a mut B,
+    capacity: usize,
+    offset: usize,
+}
+
+impl<'a, B: BufMut> Writer<'a, B> {
+    pub fn new(buf: &'a mut B) -> Self {
+        Self {
+            capacity: buf.remaining_mut(),
+            buf,
+            offset: 0,
+        }
+    }
+
+    /// The number of octets written, or the number of octets that were needed when the buffer
+    /// was too small.
+    pub fn finish(self) -> Result<usize, EncodeError> {
+        match self.offset <= self.capacity {
+            true => Ok(self.offset),
+            false => Err(EncodeError::Overflow {
+                length: self.offset,
+                capacity: self.capacity,
+            }),
+        }
+    }
+}
+
+impl<B: BufMut> Sink for Writer<'_, B> {
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn put_slice(&mut self, bytes: &[u8]) {
+        self.offset += bytes.len();
+        if self.offset <= self.capacity {
+            self.buf.put_slice(bytes);
+        }
+    }
+}
+
+/// Writing the wire format into a [`BufMut`] without allocating intermediate buffers.
+pub trait Encode {
+    /// Writes the wire format into `buf`, returning the number of octets written.
+    ///
+    /// When `buf` is too small, or some data can't be written, it's left holding the beginning of
+    /// the wire format.
+    fn encode<B: BufMut>(&self, buf: &mut B) -> Result<usize, EncodeError>;
+}
+
+impl Encode for Message {
+    /// Domain names are compressed, as with `Vec::try_from`.
+    fn encode<B: BufMut>(&self, buf: &mut B) -> Result<usize, EncodeError> {
+        let mut writer = Writer::new(buf);
+        self.write(&mut writer)?;
+        writer.finish()
+    }
+}
+
+impl Encode for Header {
+    fn encode<B: BufMut>(&self, buf: &mut B) -> Result<usize, EncodeError> {
+        let mut writer = Writer::new(buf);
+        self.write(&mut writer);
+        writer.finish()
+    }
+}
+
+impl Encode for Question {
+    fn encode<B: BufMut>(&self, buf: &mut B) -> Result<usize, EncodeError> {
+        let mut writer = Writer::new(buf);
+        self.write(&mut writer, None);
+        writer.finish()
+    }
+}
+
+impl Encode for ResourceRecord {
+    fn encode<B: BufMut>(&self, buf: &mut B) -> Result<usize, EncodeError> {
+        let mut writer = Writer::new(buf);
+        self.write(&mut writer, None)?;
+        writer.finish()
+    }
+}
+
+impl Encode for ResourceData {
+    fn encode<B: BufMut>(&self, buf: &mut B) -> Result<usize, EncodeError> {
+        let mut writer = Writer::new(buf);
+        self.write(&mut writer, None)?;
+        writer.finish()
+    }
+}
+
+impl Encode for Label {
+    fn encode<B: BufMut>(&self, buf: &mut B) -> Result<usize, EncodeError> {
+        let mut writer = Writer::new(buf);
+        self.write(&mut writer, None);
+        writer.finish()
+    }
+}
+
+impl Encode for CharacterString {
+    fn encode<B: BufMut>(&self, buf: &mut B) -> Result<usize, EncodeError> {
+        let mut writer = Writer::new(buf);
+        self.write(&mut writer);
+        writer.finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The buffer has room for `capacity` octets, but `length` are needed
+    Overflow { length: usize, capacity: usize },
+    /// A section holds more entries than the header can count
+    TooManyEntries { section: Section, entries: usize },
+    /// The data of a record can't be written, e.g. it's longer than its length field can count
+    Data(ResourceDataError),
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::Overflow { length, capacity } => format!(
+                "encoding needs '{length}' bytes, but the buffer only has room for '{capacity}'"
+            )
+            .fmt(f),
+            EncodeError::TooManyEntries { section, entries } => format!(
+                "the {section} section holds '{entries}' entries, but the header can only count '{}'",
+                u16::MAX
+            )
+            .fmt(f),
+            EncodeError::Data(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for EncodeError {}
+
+impl From<ResourceDataError> for EncodeError {
+    fn from(value: ResourceDataError) -> Self {
+        Self::Data(value)
+    }
+}
+
+#[cfg(test)]
+mod encoding {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::message::{Edns, QuestionClass, QuestionType, ResourceClass};
+
+    fn message() -> Message {
+        let mut message = Message::new(1234);
+        message
+            .ask("codecrafters.io", QuestionType::MX, QuestionClass::IN)
+            .unwrap();
+        message.answer(ResourceRecord {
+            name: Label::parse_str("codecrafters.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::MailExchange {
+                preference: 10,
+                exchange: Label::parse_str("mail.codecrafters.io").unwrap(),
+            },
+        });
+        message.add(ResourceRecord {
+            name: Label::parse_str("mail.codecrafters.io").unwrap(),
+            class: ResourceClass::IN,
+            time_to_live: 60,
+            data: ResourceData::Address(Ipv4Addr::new(8, 8, 8, 8)),
+        });
+        message.set_edns(Some(Edns::new(1232)));
+        message
+    }
+
+    #[test]
+    fn same_as_vectors() {
+        let message = message();
+        let expected = Vec::try_from(message.clone()).unwrap();
+
+        let mut buf = [0u8; 512];
+        let length = message.encode(&mut &mut buf[..]).unwrap();
+        assert_eq!(buf[..length], expected[..]);
+
+        let mut buf = vec![0xff];
+        message.encode(&mut buf).unwrap();
+        assert_eq!(buf[1..], expected[..]);
+
+        let record = &message.answers[0];
+        let mut buf = [0u8; 64];
+        let length = record.encode(&mut &mut buf[..]).unwrap();
+        assert_eq!(buf[..length], Vec::try_from(record.clone()).unwrap()[..]);
+    }
+
+    #[test]
+    fn report_overflow() {
+        let message = message();
+        let length = Vec::try_from(message.clone()).unwrap().len();
+
+        let mut buf = [0u8; 32];
+        assert_eq!(
+            message.encode(&mut &mut buf[..]),
+            Err(EncodeError::Overflow {
+                length,
+                capacity: 32
+            })
+        );
+
+        let mut buf = [0u8; 12];
+        assert_eq!(message.header.encode(&mut &mut buf[..]), Ok(12));
+        assert_eq!(
+            message.header.encode(&mut &mut buf[..11]),
+            Err(EncodeError::Overflow {
+                length: 12,
+                capacity: 11
+            })
+        );
+    }
+
+    #[test]
+    fn reject_oversized_data() {
+        let mut message = message();
+        message.answers[0].data = ResourceData::Null(vec![0; u16::MAX as usize + 1]);
+
+        let mut buf = vec![];
+        assert_eq!(
+            message.encode(&mut buf),
+            Err(EncodeError::Data(ResourceDataError::FieldTooLong {
+                max: u16::MAX as usize,
+                found: u16::MAX as usize + 1
+            }))
+        );
+
+        message.answers[0].data = ResourceData::Null(vec![0; u16::MAX as usize]);
+        assert!(message.encode(&mut buf).is_ok());
+    }
+}
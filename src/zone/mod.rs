@@ -0,0 +1,19 @@
+//! Master files ([RFC1035 5]) hold the records of a zone as text, one [`ResourceRecord`] per
+//! entry:
+//!
+//! ```txt
+//! $ORIGIN example.com.
+//! $TTL 1h
+//! @       IN  SOA ns admin ( 2024010101 7200 900 1209600 300 )
+//!             NS  ns
+//! ns          A   192.0.2.53 ; the name server
+//! ```
+//!
+//! [RFC1035 5]: <https://datatracker.ietf.org/doc/html/rfc1035#section-5>
+//! [`ResourceRecord`]: crate::message::ResourceRecord
+
+pub mod parser;
+pub mod writer;
+
+pub use parser::*;
+pub use writer::*;
@@ -0,0 +1,585 @@
+//! Reads the records of a master file. Each entry is a directive or a record:
+//!
+//! ```txt
+//! $ORIGIN <domain-name> [<comment>]
+//! $INCLUDE <file-name> [<domain-name>] [<comment>]
+//! $TTL <TTL> [<comment>]
+//! <domain-name> <rr> [<comment>]
+//! <blank> <rr> [<comment>]
+//! ```
+//!
+//! where `<rr>` is `[<TTL>] [<class>] <type> <RDATA>`, with the TTL and class in either order.
+//! Omitted owners, TTLs and classes are those of the previous record, except for the TTL which is
+//! given by `$TTL` when it's present ([RFC2308 4]). Parentheses let an entry span several lines,
+//! and `;` starts a comment running to the end of the line.
+//!
+//! [RFC2308 4]: <https://datatracker.ietf.org/doc/html/rfc2308#section-4>
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::message::{
+    Label, LabelError, ResourceClass, ResourceData, ResourceDataError, ResourceRecord, ResourceType,
+};
+
+/// How deep `$INCLUDE` directives may be nested, which stops a file from including itself forever.
+pub const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Parses the records of a master file whose names are relative to `origin` until a `$ORIGIN`
+/// directive is met. Files named by `$INCLUDE` are relative to the working directory.
+pub fn parse_zone(text: &str, origin: &Label) -> Result<Vec<ResourceRecord>, ZoneError> {
+    let mut parser = Parser::new(origin);
+    parser.parse(text, None)?;
+    Ok(parser.records)
+}
+
+/// Parses the records of the master file at `path`, see [`parse_zone`]. Files named by `$INCLUDE`
+/// are relative to the directory of the file including them.
+pub fn parse_zone_file(
+    path: impl AsRef<Path>,
+    origin: &Label,
+) -> Result<Vec<ResourceRecord>, ZoneError> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|err| ZoneError {
+        file: Some(path.to_owned()),
+        line: 0,
+        kind: ZoneErrorKind::Io(err.kind()),
+    })?;
+
+    let mut parser = Parser::new(origin);
+    parser.parse(&text, Some(path))?;
+    Ok(parser.records)
+}
+
+/// Reads a TTL, either as a number of seconds or as a sequence of numbers with a unit among `w`,
+/// `d`, `h`, `m` and `s`, e.g. `1h30m`.
+pub fn parse_time_to_live(value: &str) -> Option<u32> {
+    if value.bytes().all(|c| c.is_ascii_digit()) {
+        return value.parse().ok();
+    }
+
+    let mut total = 0u32;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        let number = rest[..digits].parse::<u32>().ok()?;
+        let unit = match rest[digits..].bytes().next()?.to_ascii_lowercase() {
+            b'w' => 604800,
+            b'd' => 86400,
+            b'h' => 3600,
+            b'm' => 60,
+            b's' => 1,
+            _ => return None,
+        };
+        total = total.checked_add(number.checked_mul(unit)?)?;
+        rest = &rest[digits + 1..];
+    }
+
+    Some(total)
+}
+
+/// A directive or a record, gathered over the lines spanned by its parentheses.
+#[derive(Debug, Default)]
+struct Entry<'a> {
+    /// The line the entry starts on
+    line: usize,
+    /// Whether the line starts with a blank, i.e. the owner is omitted
+    blank: bool,
+    tokens: Vec<&'a str>,
+}
+
+/// Splits `text` into entries, dropping comments and parentheses.
+fn split_entries(text: &str) -> Result<Vec<Entry<'_>>, (usize, ZoneErrorKind)> {
+    let mut entries = vec![];
+    let mut entry = Entry::default();
+    let mut line = 1;
+    let mut start = None;
+    let mut depth = 0usize;
+    let mut depth_line = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut comment = false;
+    let mut line_start = true;
+
+    // the final newline flushes the last entry
+    for (index, c) in text.char_indices().chain([(text.len(), '\n')]) {
+        let first = std::mem::replace(&mut line_start, c == '\n');
+
+        let separator = match c {
+            '\n' if quoted => return Err((line, ZoneErrorKind::UnterminatedString)),
+            _ if comment && c != '\n' => continue,
+            _ if escaped => {
+                escaped = false;
+                line += (c == '\n') as usize;
+                false
+            }
+            '\\' => {
+                escaped = true;
+                false
+            }
+            '"' => {
+                quoted = !quoted;
+                false
+            }
+            _ if quoted => false,
+            _ => matches!(c, ';' | '(' | ')') || c.is_whitespace(),
+        };
+
+        if separator {
+            if let Some(start) = start.take() {
+                entry.tokens.push(&text[start..index]);
+            }
+
+            match c {
+                ';' => comment = true,
+                '(' => {
+                    if depth == 0 {
+                        depth_line = line;
+                    }
+                    depth += 1;
+                }
+                ')' => {
+                    depth = depth
+                        .checked_sub(1)
+                        .ok_or((line, ZoneErrorKind::UnbalancedParentheses))?
+                }
+                '\n' => {
+                    comment = false;
+                    if depth == 0 {
+                        let entry = std::mem::take(&mut entry);
+                        if !entry.tokens.is_empty() {
+                            entries.push(entry);
+                        }
+                    }
+                    line += 1;
+                }
+                _ => {
+                    if first && entry.tokens.is_empty() && depth == 0 {
+                        entry.blank = true;
+                    }
+                }
+            }
+            continue;
+        }
+
+        if start.is_none() {
+            if entry.tokens.is_empty() {
+                entry.line = line;
+            }
+            start = Some(index);
+        }
+    }
+
+    // only an escape may have swallowed the final newline
+    match depth {
+        _ if start.is_some() => Err((line, ZoneErrorKind::Syntax("\\".to_owned()))),
+        0 => Ok(entries),
+        _ => Err((depth_line, ZoneErrorKind::UnbalancedParentheses)),
+    }
+}
+
+/// The state carried from one entry to the next.
+struct Parser {
+    origin: Label,
+    /// The TTL given by `$TTL`
+    default_time_to_live: Option<u32>,
+    owner: Option<Label>,
+    time_to_live: Option<u32>,
+    class: Option<ResourceClass>,
+    depth: usize,
+    records: Vec<ResourceRecord>,
+}
+
+impl Parser {
+    fn new(origin: &Label) -> Self {
+        Self {
+            origin: origin.clone(),
+            default_time_to_live: None,
+            owner: None,
+            time_to_live: None,
+            class: None,
+            depth: 0,
+            records: vec![],
+        }
+    }
+
+    fn parse(&mut self, text: &str, file: Option<&Path>) -> Result<(), ZoneError> {
+        let at = |line, kind| ZoneError {
+            file: file.map(Path::to_owned),
+            line,
+            kind,
+        };
+
+        for entry in split_entries(text).map_err(|(line, kind)| at(line, kind))? {
+            let result = match entry.tokens[0] {
+                directive if directive.starts_with('$') && !entry.blank => {
+                    self.directive(directive, &entry.tokens[1..], file)
+                }
+                _ => self.record(&entry),
+            };
+            result.map_err(|kind| at(entry.line, kind))?;
+        }
+
+        Ok(())
+    }
+
+    fn directive(
+        &mut self,
+        directive: &str,
+        arguments: &[&str],
+        file: Option<&Path>,
+    ) -> Result<(), ZoneErrorKind> {
+        match (directive.to_ascii_uppercase().as_str(), arguments) {
+            ("$ORIGIN", [origin]) => self.origin = Label::parse_relative(origin, &self.origin)?,
+            ("$TTL", [ttl]) => {
+                self.default_time_to_live = Some(
+                    parse_time_to_live(ttl)
+                        .ok_or_else(|| ZoneErrorKind::Syntax(ttl.to_string()))?,
+                )
+            }
+            ("$INCLUDE", [path, origin @ ..]) if origin.len() <= 1 => {
+                if self.depth >= MAX_INCLUDE_DEPTH {
+                    return Err(ZoneErrorKind::IncludeDepth);
+                }
+
+                let path = path
+                    .strip_prefix('"')
+                    .and_then(|path| path.strip_suffix('"'))
+                    .unwrap_or(path);
+                let path = match file.and_then(Path::parent) {
+                    Some(directory) => directory.join(path),
+                    None => PathBuf::from(path),
+                };
+                let text =
+                    fs::read_to_string(&path).map_err(|err| ZoneErrorKind::Io(err.kind()))?;
+
+                // the origin and owner of the including file are restored after the included one
+                let origin = match origin.first() {
+                    Some(origin) => Label::parse_relative(origin, &self.origin)?,
+                    None => self.origin.clone(),
+                };
+                let origin = std::mem::replace(&mut self.origin, origin);
+                let owner = self.owner.clone();
+
+                self.depth += 1;
+                let result = self.parse(&text, Some(&path));
+                self.depth -= 1;
+
+                self.origin = origin;
+                self.owner = owner;
+                result.map_err(|err| ZoneErrorKind::Include(Box::new(err)))?;
+            }
+            ("$ORIGIN" | "$TTL" | "$INCLUDE", _) => {
+                return Err(ZoneErrorKind::Syntax(arguments.join(" ")))
+            }
+            _ => return Err(ZoneErrorKind::UnknownDirective(directive.to_owned())),
+        }
+
+        Ok(())
+    }
+
+    fn record(&mut self, entry: &Entry) -> Result<(), ZoneErrorKind> {
+        let mut tokens = entry.tokens.as_slice();
+        let name = match entry.blank {
+            true => self.owner.clone().ok_or(ZoneErrorKind::MissingOwner)?,
+            false => {
+                let (name, rest) = tokens.split_first().ok_or(ZoneErrorKind::MissingOwner)?;
+                tokens = rest;
+                Label::parse_relative(name, &self.origin)?
+            }
+        };
+
+        let mut time_to_live = None;
+        let mut class = None;
+        while let Some((token, rest)) = tokens.split_first() {
+            match parse_time_to_live(token) {
+                Some(ttl) if time_to_live.is_none() => time_to_live = Some(ttl),
+                _ => match token.parse::<ResourceClass>() {
+                    Ok(value) if class.is_none() => class = Some(value),
+                    _ => break,
+                },
+            }
+            tokens = rest;
+        }
+
+        let (typ, tokens) = tokens.split_first().ok_or(ResourceDataError::Truncated)?;
+        let typ = typ
+            .parse::<ResourceType>()
+            .map_err(|_| ZoneErrorKind::Syntax(typ.to_string()))?;
+        let data = ResourceData::from_tokens(typ, tokens, Some(&self.origin))?;
+
+        // the SOA record may fall back on its minimum field ([RFC2308 4])
+        let minimum = match data {
+            ResourceData::SOA { minimum, .. } => Some(minimum),
+            _ => None,
+        };
+        let time_to_live = time_to_live
+            .or(self.default_time_to_live)
+            .or(self.time_to_live)
+            .or(minimum)
+            .ok_or(ZoneErrorKind::MissingTimeToLive)?;
+        let class = class.or(self.class).unwrap_or(ResourceClass::IN);
+
+        self.owner = Some(name.clone());
+        self.time_to_live = Some(time_to_live);
+        self.class = Some(class);
+        self.records.push(ResourceRecord {
+            name,
+            class,
+            time_to_live,
+            data,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZoneError {
+    /// The file holding the faulty entry, if it was read from one
+    pub file: Option<PathBuf>,
+    /// The line the faulty entry starts on, counting from 1
+    pub line: usize,
+    pub kind: ZoneErrorKind,
+}
+
+impl Display for ZoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}: {}", file.display(), self.line, self.kind),
+            None => write!(f, "line {}: {}", self.line, self.kind),
+        }
+    }
+}
+
+impl Error for ZoneError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZoneErrorKind {
+    /// A file couldn't be read
+    Io(io::ErrorKind),
+    /// A `)` doesn't close any `(`, or a `(` is never closed
+    UnbalancedParentheses,
+    /// A quoted string runs past the end of its line
+    UnterminatedString,
+    UnknownDirective(String),
+    /// The first record omits its owner
+    MissingOwner,
+    /// A record omits its TTL, and neither `$TTL` nor a previous record gives it
+    MissingTimeToLive,
+    /// `$INCLUDE` directives are nested more than [`MAX_INCLUDE_DEPTH`] times
+    IncludeDepth,
+    /// An unexpected token was found
+    Syntax(String),
+    Label(LabelError),
+    Data(ResourceDataError),
+    /// An error in a file named by `$INCLUDE`
+    Include(Box<ZoneError>),
+}
+
+impl Display for ZoneErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ZoneErrorKind::*;
+        match self {
+            Io(kind) => format!("cannot read the zone file: {kind}").fmt(f),
+            UnbalancedParentheses => "parentheses are unbalanced".fmt(f),
+            UnterminatedString => "quoted string runs past the end of the line".fmt(f),
+            UnknownDirective(directive) => format!("unknown directive '{directive}'").fmt(f),
+            MissingOwner => "the first record must have an owner name".fmt(f),
+            MissingTimeToLive => "record doesn't specify a TTL, and there is no default".fmt(f),
+            IncludeDepth => {
+                format!("$INCLUDE is nested more than {MAX_INCLUDE_DEPTH} times").fmt(f)
+            }
+            Syntax(token) => format!("unexpected '{token}'").fmt(f),
+            Label(err) => err.fmt(f),
+            Data(err) => err.fmt(f),
+            Include(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for ZoneErrorKind {}
+
+impl From<LabelError> for ZoneErrorKind {
+    fn from(value: LabelError) -> Self {
+        Self::Label(value)
+    }
+}
+
+impl From<ResourceDataError> for ZoneErrorKind {
+    fn from(value: ResourceDataError) -> Self {
+        Self::Data(value)
+    }
+}
+
+#[cfg(test)]
+mod parsing {
+    use super::*;
+
+    fn name(value: &str) -> Label {
+        value.parse().unwrap()
+    }
+
+    fn records(text: &str) -> Vec<String> {
+        parse_zone(text, &name("."))
+            .unwrap()
+            .iter()
+            .map(ResourceRecord::to_string)
+            .collect()
+    }
+
+    fn error(text: &str) -> (usize, ZoneErrorKind) {
+        let err = parse_zone(text, &name(".")).unwrap_err();
+        (err.line, err.kind)
+    }
+
+    #[test]
+    fn sample_zone() {
+        let zone = r#"
+$ORIGIN example.com.
+$TTL 1h
+; the apex of the zone
+@       IN  SOA ns admin (
+                2024010101 ; serial
+                7200       ; refresh
+                900 1209600 300 )
+            NS  ns
+        30m MX  10 mail.other.
+ns          A   192.0.2.53
+www     CH  TXT "a ; (b" c\;d
+"#;
+
+        assert_eq!(
+            records(zone),
+            [
+                "example.com. 3600 IN SOA ns.example.com. admin.example.com. 2024010101 7200 900 1209600 300",
+                "example.com. 3600 IN NS ns.example.com.",
+                "example.com. 1800 IN MX 10 mail.other.",
+                "ns.example.com. 3600 IN A 192.0.2.53",
+                "www.example.com. 3600 CH TXT \"a ; (b\" \"c;d\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn inherited_fields() {
+        assert_eq!(
+            records("a.example. 300 CH A 10.0.0.1\n 600 A 10.0.0.2\n\tIN A 10.0.0.3\nb.example. A 10.0.0.4"),
+            [
+                "a.example. 300 CH A 10.0.0.1",
+                "a.example. 600 CH A 10.0.0.2",
+                "a.example. 600 IN A 10.0.0.3",
+                "b.example. 600 IN A 10.0.0.4",
+            ]
+        );
+
+        // without $TTL nor a previous TTL, the SOA falls back on its minimum
+        assert_eq!(
+            records("example. SOA ns. admin. 1 2 3 4 5\nns.example. A 10.0.0.1"),
+            [
+                "example. 5 IN SOA ns. admin. 1 2 3 4 5",
+                "ns.example. 5 IN A 10.0.0.1",
+            ]
+        );
+    }
+
+    #[test]
+    fn time_to_live_units() {
+        assert_eq!(parse_time_to_live("300"), Some(300));
+        assert_eq!(parse_time_to_live("1h30m"), Some(5400));
+        assert_eq!(parse_time_to_live("1W2d"), Some(777600));
+        assert_eq!(parse_time_to_live("1h30"), None);
+        assert_eq!(parse_time_to_live("h"), None);
+        assert_eq!(parse_time_to_live("99999w"), None);
+    }
+
+    #[test]
+    fn line_numbered_errors() {
+        assert_eq!(
+            error("a. 1 A 10.0.0.1\n\nb. 1 A 10.0.0"),
+            (
+                3,
+                ZoneErrorKind::Data(ResourceDataError::Syntax("10.0.0".to_owned()))
+            )
+        );
+        assert_eq!(error(" 1 A 10.0.0.1"), (1, ZoneErrorKind::MissingOwner));
+        assert_eq!(
+            error("a. A 10.0.0.1"),
+            (1, ZoneErrorKind::MissingTimeToLive)
+        );
+        assert_eq!(
+            error("a. 1 A 10.0.0.1\nb. 1 SOA ns. admin. (\n1 2 3 4 5"),
+            (2, ZoneErrorKind::UnbalancedParentheses)
+        );
+        assert_eq!(
+            error("a. 1 A 10.0.0.1 )"),
+            (1, ZoneErrorKind::UnbalancedParentheses)
+        );
+        assert_eq!(
+            error("a. 1 TXT \"open\nb. 1 A 10.0.0.1"),
+            (1, ZoneErrorKind::UnterminatedString)
+        );
+        assert_eq!(
+            error("\n$GENERATE 1-2 a$ A 10.0.0.$"),
+            (2, ZoneErrorKind::UnknownDirective("$GENERATE".to_owned()))
+        );
+        assert_eq!(
+            error("a. 1 FOO bar"),
+            (1, ZoneErrorKind::Syntax("FOO".to_owned()))
+        );
+    }
+
+    #[test]
+    fn include_files() {
+        let directory = std::env::temp_dir().join(format!("zone-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("main.zone"),
+            "$ORIGIN example.\n$TTL 60\n@ NS ns\n$INCLUDE hosts.zone sub\n A 10.0.0.1\n$INCLUDE bad.zone\n",
+        )
+        .unwrap();
+        fs::write(directory.join("hosts.zone"), "ns A 10.0.0.53\n").unwrap();
+        fs::write(directory.join("bad.zone"), "\n\nns A\n").unwrap();
+
+        let err = parse_zone_file(directory.join("main.zone"), &name(".")).unwrap_err();
+        assert_eq!(err.line, 6);
+        let ZoneErrorKind::Include(inner) = &err.kind else {
+            panic!("expected an error in the included file, but found {err:?}");
+        };
+        assert_eq!(inner.file, Some(directory.join("bad.zone")));
+        assert_eq!(inner.line, 3);
+
+        fs::write(directory.join("bad.zone"), "").unwrap();
+        let records = parse_zone_file(directory.join("main.zone"), &name("."))
+            .unwrap()
+            .iter()
+            .map(ResourceRecord::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            [
+                "example. 60 IN NS ns.example.",
+                "ns.sub.example. 60 IN A 10.0.0.53",
+                "example. 60 IN A 10.0.0.1",
+            ]
+        );
+
+        fs::write(directory.join("bad.zone"), "$INCLUDE bad.zone").unwrap();
+        let err = parse_zone_file(directory.join("bad.zone"), &name(".")).unwrap_err();
+        let mut depth = 0;
+        let mut kind = &err.kind;
+        while let ZoneErrorKind::Include(inner) = kind {
+            depth += 1;
+            kind = &inner.kind;
+        }
+        assert_eq!(
+            (depth, kind),
+            (MAX_INCLUDE_DEPTH, &ZoneErrorKind::IncludeDepth)
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
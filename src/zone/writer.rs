@@ -0,0 +1,111 @@
+//! Writes records back into a master file that [`parse_zone`][super::parse_zone] reads, e.g.
+//!
+//! ```txt
+//! $ORIGIN example.com.
+//! @    3600 IN SOA ns admin 2024010101 7200 900 1209600 300
+//!      3600 IN NS  ns
+//! ns   3600 IN A   192.0.2.53
+//! www  300  IN TXT "hello"
+//! ```
+use std::fmt::{self, Write};
+
+use crate::message::{Label, ResourceRecord, ResourceType};
+
+/// Writes `records` as a master file with names relative to `origin`.
+///
+/// The SOA records come first, then the others ordered canonically by owner ([RFC4034 6.1]) and
+/// by type, the records of an RRset keeping their relative order. An owner is only written on the
+/// first of its records, and the columns are aligned.
+///
+/// [RFC4034 6.1]: <https://datatracker.ietf.org/doc/html/rfc4034#section-6.1>
+pub fn write_zone(f: &mut impl Write, records: &[ResourceRecord], origin: &Label) -> fmt::Result {
+    let mut records = records.iter().collect::<Vec<_>>();
+    records.sort_by(|a, b| {
+        (a.typ() != ResourceType::SOA)
+            .cmp(&(b.typ() != ResourceType::SOA))
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| u16::from(a.typ()).cmp(&u16::from(b.typ())))
+    });
+
+    let rows = records
+        .iter()
+        .enumerate()
+        .map(|(index, record)| {
+            let owner = match index > 0 && records[index - 1].name == record.name {
+                true => String::new(),
+                false => record.name.relative_to(origin).to_string(),
+            };
+            [
+                owner,
+                record.time_to_live.to_string(),
+                record.class.to_string(),
+                record.typ().to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = [0; 4];
+    for row in rows.iter() {
+        for (width, column) in widths.iter_mut().zip(row.iter()) {
+            *width = column.len().max(*width);
+        }
+    }
+
+    writeln!(f, "$ORIGIN {origin}")?;
+    for (row, record) in rows.iter().zip(records.iter()) {
+        let mut line = String::new();
+        for (width, column) in widths.iter().zip(row.iter()) {
+            write!(line, "{column:<width$} ")?;
+        }
+        write!(line, "{}", record.data.relative_to(origin))?;
+        writeln!(f, "{}", line.trim_end())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod writing {
+    use super::*;
+    use crate::zone::parse_zone;
+
+    #[test]
+    fn sorted_and_aligned() {
+        let origin = "example.com".parse().unwrap();
+        let records = parse_zone(
+            r#"
+$ORIGIN example.com.
+www         300 IN TXT "hello"
+mail.other. 60  IN A   192.0.2.25
+@           60  IN MX  10 mail.other.
+ns          60  IN A   192.0.2.53
+www         300 IN A   192.0.2.80
+@           60  IN NS  ns
+@           60  IN SOA ns admin 1 7200 900 1209600 300
+www         300 IN TXT "world"
+"#,
+            &origin,
+        )
+        .unwrap();
+
+        let mut zone = String::new();
+        write_zone(&mut zone, &records, &origin).unwrap();
+        assert_eq!(
+            zone,
+            "$ORIGIN example.com.
+@           60  IN SOA ns admin 1 7200 900 1209600 300
+            60  IN NS  ns
+            60  IN MX  10 mail.other.
+ns          60  IN A   192.0.2.53
+www         300 IN A   192.0.2.80
+            300 IN TXT \"hello\"
+            300 IN TXT \"world\"
+mail.other. 60  IN A   192.0.2.25
+"
+        );
+
+        let parsed = parse_zone(&zone, &Label::default()).unwrap();
+        assert_eq!(parsed.len(), records.len());
+        assert!(records.iter().all(|record| parsed.contains(record)));
+    }
+}